@@ -1,23 +1,101 @@
-use crate::interfaces::cli::Cli;
+use super::{BackoffStrategy, InputFormat, JudgeFormat, JudgeUrl};
+use crate::core::domain::error::FailureKind;
+use crate::interfaces::cli::{Cli, STDIN_PATH};
+use std::io::{self, IsTerminal};
+use std::net::IpAddr;
 use std::path::PathBuf;
+use url::Url;
 
 #[derive(Debug, Clone)]
 pub struct AppInputDTO {
-    pub file: Option<PathBuf>,
+    pub files: Vec<PathBuf>,
+    pub stdin: bool,
+    pub format: InputFormat,
     pub proxies: Option<String>,
+    pub urls: Vec<Url>,
+    pub url_header: Option<(String, String)>,
+    pub url_timeout: u64,
     pub timeout: u8,
     pub max_concurrent: usize,
+    pub deadline: u16,
+    pub scheme_concurrency: usize,
+    pub all_schemes: bool,
+    pub samples: u8,
+    pub throughput: Option<u64>,
+    pub throughput_url: Option<Url>,
+    pub upload: bool,
+    pub upload_url: Option<Url>,
+    pub throughput_budget: Option<u64>,
+    pub score_throughput: bool,
+    pub max_attempts: u8,
+    pub backoff: BackoffStrategy,
+    pub backoff_base: u64,
+    pub jitter: bool,
+    pub retry_on: Vec<FailureKind>,
     pub out_dir: Option<PathBuf>,
+    pub countries: Vec<String>,
+    pub rejects: Option<PathBuf>,
+    pub strict: bool,
+    pub plain: bool,
+    pub dedup_resolve: bool,
+    pub socks_probe: bool,
+    pub judges: Vec<JudgeUrl>,
+    pub ip_judges: Vec<JudgeUrl>,
+    pub judge_format: JudgeFormat,
+    pub real_ips: Vec<IpAddr>,
+    pub real_ip_urls: Vec<Url>,
+    pub geoip: Option<PathBuf>,
+    pub judge_insecure: bool,
 }
 
 impl AppInputDTO {
     pub fn from_cli(cli: Cli) -> Self {
         Self {
-            file: cli.file,
+            stdin: cli.file.iter().any(|f| f.as_os_str() == STDIN_PATH),
+            files: cli
+                .file
+                .into_iter()
+                .filter(|f| f.as_os_str() != STDIN_PATH)
+                .collect(),
+            format: cli.format,
             proxies: cli.proxies,
+            urls: cli.url,
+            url_header: cli.url_header.as_deref().and_then(|h| {
+                h.split_once(':')
+                    .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            }),
+            url_timeout: cli.url_timeout as u64,
             timeout: cli.timeout,
             max_concurrent: cli.max_concurrent as usize,
+            deadline: cli.deadline,
+            scheme_concurrency: cli.scheme_concurrency as usize,
+            all_schemes: cli.all_schemes,
+            samples: cli.samples,
+            throughput: cli.throughput,
+            throughput_url: cli.throughput_url,
+            upload: cli.upload,
+            upload_url: cli.upload_url,
+            throughput_budget: cli.throughput_budget,
+            score_throughput: cli.score_throughput,
+            max_attempts: cli.max_attempts,
+            backoff: cli.backoff,
+            backoff_base: cli.backoff_base as u64,
+            jitter: cli.jitter,
+            retry_on: cli.retry_on,
             out_dir: cli.out_dir,
+            countries: cli.country,
+            rejects: cli.rejects,
+            strict: cli.strict,
+            plain: cli.plain || !io::stdout().is_terminal(),
+            dedup_resolve: cli.dedup_resolve,
+            socks_probe: cli.socks_probe,
+            judges: cli.judge,
+            ip_judges: cli.ip_judge,
+            judge_format: cli.judge_format,
+            real_ips: cli.real_ip,
+            real_ip_urls: cli.real_ip_url,
+            geoip: cli.geoip,
+            judge_insecure: cli.judge_insecure,
         }
     }
 }
@@ -0,0 +1,39 @@
+use std::fmt;
+
+/// How the pause between two attempts grows with the number of failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackoffStrategy {
+    /// Always the base delay
+    Constant,
+    /// Base delay times the number of failed attempts
+    Linear,
+    /// Base delay doubled after every failed attempt
+    Exponential,
+}
+
+impl BackoffStrategy {
+    pub fn get_all_strategy() -> Vec<BackoffStrategy> {
+        vec![Self::Constant, Self::Linear, Self::Exponential]
+    }
+
+    /// string → enum
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "constant" | "fixed" => Some(Self::Constant),
+            "linear" => Some(Self::Linear),
+            "exponential" | "exp" => Some(Self::Exponential),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BackoffStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Constant => "constant",
+            Self::Linear => "linear",
+            Self::Exponential => "exponential",
+        };
+        write!(f, "{}", s)
+    }
+}
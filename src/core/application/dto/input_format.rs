@@ -0,0 +1,43 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Auto,
+    /// scheme://[user:pass@]host:port
+    Plain,
+    /// host:port:user:pass
+    Colon,
+    Csv,
+    Jsonl,
+}
+
+impl InputFormat {
+    pub fn get_all_format() -> Vec<InputFormat> {
+        vec![Self::Auto, Self::Plain, Self::Colon, Self::Csv, Self::Jsonl]
+    }
+
+    /// string → enum
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "plain" => Some(Self::Plain),
+            "colon" => Some(Self::Colon),
+            "csv" => Some(Self::Csv),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for InputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Auto => "auto",
+            Self::Plain => "plain",
+            Self::Colon => "colon",
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+        };
+        write!(f, "{}", s)
+    }
+}
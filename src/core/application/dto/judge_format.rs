@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// Shape of the responses returned by judge (IP/header echo) endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JudgeFormat {
+    Auto,
+    /// Bare IP address, or `Name: value` header lines
+    Plain,
+    /// httpbin-style JSON: `{"origin": ...}`, `{"ip": ...}`, `{"headers": {...}}`
+    Httpbin,
+    /// azenv-style `KEY = value` dumps of the CGI environment
+    Azenv,
+}
+
+impl JudgeFormat {
+    pub fn get_all_format() -> Vec<JudgeFormat> {
+        vec![Self::Auto, Self::Plain, Self::Httpbin, Self::Azenv]
+    }
+
+    /// string → enum
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "plain" => Some(Self::Plain),
+            "httpbin" | "json" => Some(Self::Httpbin),
+            "azenv" => Some(Self::Azenv),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JudgeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Auto => "auto",
+            Self::Plain => "plain",
+            Self::Httpbin => "httpbin",
+            Self::Azenv => "azenv",
+        };
+        write!(f, "{}", s)
+    }
+}
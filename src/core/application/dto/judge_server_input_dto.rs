@@ -0,0 +1,22 @@
+use crate::interfaces::cli::JudgeArgs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
+pub struct JudgeServerInputDTO {
+    pub listen: SocketAddr,
+    pub tls_listen: Option<SocketAddr>,
+    pub cert: Option<(PathBuf, PathBuf)>,
+    pub tls_names: Vec<String>,
+}
+
+impl JudgeServerInputDTO {
+    pub fn from_args(args: JudgeArgs) -> Self {
+        Self {
+            listen: args.listen,
+            tls_listen: args.tls_listen,
+            cert: args.cert.zip(args.key),
+            tls_names: args.tls_name,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::core::domain::ProxyScheme;
+use url::Url;
+
+/// A judge endpoint, optionally restricted to proxies tested with one scheme.
+#[derive(Debug, Clone)]
+pub struct JudgeUrl {
+    pub scheme: Option<ProxyScheme>,
+    pub url: Url,
+}
+
+impl JudgeUrl {
+    /// [scheme=]url, e.g. `socks5=https://judge.local/azenv.php`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (scheme, url) = match s.split_once('=') {
+            Some((prefix, rest)) if ProxyScheme::parse(prefix).is_some() => {
+                (ProxyScheme::parse(prefix), rest)
+            }
+            _ => (None, s),
+        };
+
+        let url = Url::parse(url).map_err(|e| format!("invalid judge URL '{}': {}", url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "invalid judge URL '{}': only http and https are allowed",
+                url
+            ));
+        }
+
+        Ok(Self { scheme, url })
+    }
+}
@@ -1,3 +1,13 @@
 mod app_input_dto;
+mod backoff_strategy;
+mod input_format;
+mod judge_format;
+mod judge_server_input_dto;
+mod judge_url;
 
 pub use app_input_dto::AppInputDTO;
+pub use backoff_strategy::BackoffStrategy;
+pub use input_format::InputFormat;
+pub use judge_format::JudgeFormat;
+pub use judge_server_input_dto::JudgeServerInputDTO;
+pub use judge_url::JudgeUrl;
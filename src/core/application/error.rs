@@ -1,11 +1,12 @@
-use crate::core::domain::Error as DomainError;
+use crate::core::domain::{Error as DomainError, RejectedLine};
+use colored::*;
+use std::fmt;
 
 #[derive(Debug, Clone)]
 pub enum Error {
-    ExternalError(String),
-    OperationFailed(String),
+    External(String),
     Domain(DomainError),
-    Unexpected,
+    Rejected(RejectedLine),
 }
 
 impl From<DomainError> for Error {
@@ -13,3 +14,17 @@ impl From<DomainError> for Error {
         Self::Domain(e)
     }
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let error_head = "Error:".red();
+
+        match self {
+            Error::External(s) => {
+                write!(f, "{} {}", error_head, s.bright_red())
+            }
+            Error::Domain(e) => write!(f, "{}", e),
+            Error::Rejected(r) => write!(f, "{} ({}, line {})", r.error(), r.source(), r.line()),
+        }
+    }
+}
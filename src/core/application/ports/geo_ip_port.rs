@@ -0,0 +1,7 @@
+use crate::core::domain::GeoLocation;
+use std::net::IpAddr;
+
+pub trait GeoIpPort: Send + Sync {
+    /// `None` for addresses the database does not cover.
+    fn locate(&self, ip: IpAddr) -> Option<GeoLocation>;
+}
@@ -0,0 +1,9 @@
+use crate::core::domain::IpAdress;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait HostResolver: Send + Sync {
+    /// Resolves a hostname to one of its addresses; IP literals and lookup
+    /// failures yield `None`.
+    async fn resolve(&self, host: &IpAdress) -> Option<IpAdress>;
+}
@@ -1,5 +1,13 @@
+mod geo_ip_port;
+mod host_resolver;
 mod proxy_repository;
 mod proxy_test_port;
+mod result_writer;
+mod socks_probe_port;
 
+pub use geo_ip_port::GeoIpPort;
+pub use host_resolver::HostResolver;
 pub use proxy_repository::ProxyRepository;
 pub use proxy_test_port::ProxyTestPort;
+pub use result_writer::ResultWriter;
+pub use socks_probe_port::SocksProbePort;
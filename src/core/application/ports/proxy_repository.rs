@@ -1,9 +1,11 @@
 use crate::core::application::Error;
-use crate::core::domain::Proxy;
+use crate::core::domain::{Proxy, RejectedLine};
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 
 #[async_trait]
-pub trait ProxyRepository {
-    async fn stream_proxies(&self, max_concurrent: usize) -> Result<mpsc::Receiver<Proxy>, Error>;
+pub trait ProxyRepository: Send + Sync {
+    /// Streams every input line in order: parsed proxies as `Ok`, lines that fail
+    /// to parse as `Err`.
+    async fn stream_proxies(&self) -> Result<mpsc::Receiver<Result<Proxy, RejectedLine>>, Error>;
 }
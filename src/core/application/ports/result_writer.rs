@@ -0,0 +1,12 @@
+use crate::core::application::Error;
+use crate::core::domain::{RejectedLine, SocksProbe, TestFailure, TestResult};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ResultWriter: Send + Sync {
+    async fn write(&self, results: &[TestResult]) -> Result<(), Error>;
+    async fn write_rejects(&self, rejects: &[&RejectedLine]) -> Result<(), Error>;
+    async fn write_failures(&self, failures: &[TestFailure]) -> Result<(), Error>;
+    /// `probes` pairs each completed handshake with the `version://host:port` it reached.
+    async fn write_socks_probes(&self, probes: &[(String, SocksProbe)]) -> Result<(), Error>;
+}
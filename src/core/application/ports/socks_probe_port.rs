@@ -0,0 +1,10 @@
+use crate::core::domain::error::SocksProbeError;
+use crate::core::domain::{Proxy, SocksProbe};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait SocksProbePort: Send + Sync {
+    /// Performs the SOCKS handshake the proxy's scheme calls for, or SOCKS5
+    /// then SOCKS4 when the scheme is unknown.
+    async fn probe(&self, proxy: &Proxy) -> Result<SocksProbe, SocksProbeError>;
+}
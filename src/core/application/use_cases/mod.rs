@@ -1,3 +1,3 @@
 mod proxy_tester;
 
-pub use proxy_tester::ProxyTester;
+pub use proxy_tester::{ProxyTester, RunOptions};
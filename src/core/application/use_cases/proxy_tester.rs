@@ -1,53 +1,426 @@
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use futures::StreamExt;
+use colored::*;
 use futures::stream::FuturesUnordered;
+use futures::{StreamExt, stream};
 use tokio::sync::Semaphore;
 
 use crate::core::application::Error as AppError;
-use crate::core::application::ports::{ProxyRepository, ProxyTestPort};
+use crate::core::application::ports::{
+    GeoIpPort, HostResolver, ProxyRepository, ProxyTestPort, ResultWriter, SocksProbePort,
+};
+use crate::core::domain::error::SocksProbeError;
+use crate::core::domain::{
+    Deduplicator, GeoLocation, ParseReport, SocksProbe, SocksReply, TestFailure, TestResult,
+};
+
+/// How a run ingests its input and reports on it.
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    max_concurrent: usize,
+    strict: bool,
+    plain: bool,
+    dedup_resolve: bool,
+    countries: Vec<String>,
+}
+
+impl RunOptions {
+    /// `plain` leaves only working proxy URLs on stdout, one per line, and
+    /// moves every other line to stderr so the output can be piped.
+    /// `dedup_resolve` dedups on resolved hostnames; a non-empty `countries`
+    /// drops proxies located elsewhere from both the console and the output.
+    pub fn new(
+        max_concurrent: usize,
+        strict: bool,
+        plain: bool,
+        dedup_resolve: bool,
+        countries: Vec<String>,
+    ) -> Self {
+        Self {
+            max_concurrent,
+            strict,
+            plain,
+            dedup_resolve,
+            countries,
+        }
+    }
+}
 
 pub struct ProxyTester {
     repo: Arc<dyn ProxyRepository>,
     tester: Arc<dyn ProxyTestPort>,
-    max_concurrent: usize,
+    writer: Option<Arc<dyn ResultWriter>>,
+    options: RunOptions,
+    resolver: Option<Arc<dyn HostResolver>>,
+    prober: Option<Arc<dyn SocksProbePort>>,
+    geo_ip: Option<Arc<dyn GeoIpPort>>,
 }
 
 impl ProxyTester {
     pub fn new(
         repo: Arc<dyn ProxyRepository>,
         tester: Arc<dyn ProxyTestPort>,
-        max_concurrent: usize,
+        writer: Option<Arc<dyn ResultWriter>>,
+        options: RunOptions,
+        resolver: Option<Arc<dyn HostResolver>>,
+        prober: Option<Arc<dyn SocksProbePort>>,
+        geo_ip: Option<Arc<dyn GeoIpPort>>,
     ) -> Self {
         Self {
             repo,
             tester,
-            max_concurrent,
+            writer,
+            options,
+            resolver,
+            prober,
+            geo_ip,
         }
     }
 
+    /// Proxies without a known country only pass an empty filter.
+    fn in_countries(&self, country: Option<&str>) -> bool {
+        self.options.countries.is_empty()
+            || country.is_some_and(|country| self.options.countries.iter().any(|c| c == country))
+    }
+
     pub async fn execute(&self) -> Result<(), AppError> {
-        let mut proxy_rx = self.repo.stream_proxies(self.max_concurrent).await?;
+        let mut proxy_rx = self.repo.stream_proxies().await?;
 
-        let semaphore = Arc::new(Semaphore::new(self.max_concurrent));
+        let semaphore = Arc::new(Semaphore::new(self.options.max_concurrent));
         let mut tasks = FuturesUnordered::new();
+        let mut report = ParseReport::default();
+        let mut dedup = Deduplicator::default();
+
+        // Hostnames are resolved ahead of the dedup, several at a time, while
+        // proxies still reach it in input order.
+        let resolver = self.resolver.clone();
+        let mut parsed_proxies = stream::poll_fn(|cx| proxy_rx.poll_recv(cx))
+            .map(|parsed| {
+                let resolver = resolver.clone();
+                async move {
+                    let resolved = match (&parsed, resolver) {
+                        (Ok(proxy), Some(resolver)) => resolver.resolve(proxy.ip()).await,
+                        _ => None,
+                    };
+                    (parsed, resolved)
+                }
+            })
+            .buffered(self.options.max_concurrent);
+
+        while let Some((parsed, resolved)) = parsed_proxies.next().await {
+            let proxy = match parsed {
+                Ok(proxy) => proxy,
+                Err(rejected) if self.options.strict => {
+                    tasks
+                        .iter()
+                        .for_each(|task: &tokio::task::JoinHandle<_>| task.abort());
+                    // The offending line still lands in the rejects file.
+                    if let Some(writer) = &self.writer {
+                        writer.write_rejects(&[&rejected]).await?;
+                    }
+                    return Err(AppError::Rejected(rejected));
+                }
+                Err(rejected) => {
+                    report.reject(rejected);
+                    continue;
+                }
+            };
+
+            // Hostnames are located by the address they resolved to.
+            let proxy_location = self
+                .geo_ip
+                .as_ref()
+                .and_then(|geo_ip| geo_ip.locate(resolved.as_ref().unwrap_or(proxy.ip()).ip()?));
+
+            if !dedup.insert(&proxy, resolved.filter(|_| self.options.dedup_resolve)) {
+                report.duplicate();
+                continue;
+            }
+            report.accept();
 
-        while let Some(proxy) = proxy_rx.recv().await {
             let permit = semaphore.clone();
             let tester = self.tester.clone();
+            let geo_ip = self.geo_ip.clone();
+            // Proxies without a scheme may still turn out to speak SOCKS.
+            let prober = self
+                .prober
+                .clone()
+                .filter(|_| proxy.scheme().as_ref().is_none_or(|s| s.is_socks()));
 
             tasks.push(tokio::spawn(async move {
                 let _permit = permit.acquire().await.ok();
-                tester.test(proxy).await.ok()
+                let probe = match prober {
+                    Some(prober) => Some((proxy.authority(), prober.probe(&proxy).await)),
+                    None => None,
+                };
+                let result = match tester.test(proxy.clone()).await {
+                    Ok(mut result) => {
+                        let exit_location = geo_ip
+                            .as_ref()
+                            .and_then(|geo_ip| geo_ip.locate(result.best().exit_ip()?));
+                        result.set_locations(proxy_location, exit_location);
+                        Ok(result)
+                    }
+                    // A refused SOCKS CONNECT says more than whatever the test ran into.
+                    Err(e) => {
+                        let e = probe
+                            .as_ref()
+                            .and_then(|(_, probe)| probe.as_ref().ok()?.failure())
+                            .unwrap_or(e);
+                        Err(TestFailure::new(proxy, e, proxy_location))
+                    }
+                };
+                (probe, result)
             }));
         }
 
+        let mut results = Vec::new();
+        let mut failures = Vec::new();
+        let mut socks_probes = Vec::new();
+
         while let Some(res) = tasks.next().await {
-            if let Ok(Some(result)) = res {
-                println!("✅ {:?}", result);
+            let Ok((probe, result)) = res else {
+                continue;
+            };
+
+            let country = match &result {
+                Ok(result) => result.country(),
+                Err(failure) => failure.country(),
+            };
+            if !self.in_countries(country) {
+                continue;
+            }
+
+            if let Some((authority, probe)) = probe {
+                self.print_socks_probe(&authority, &probe);
+                if let Ok(probe) = probe {
+                    socks_probes.push((format!("{}://{}", probe.version(), authority), probe));
+                }
+            }
+
+            match result {
+                Ok(result) => {
+                    self.print_result(&result);
+                    results.push(result);
+                }
+                Err(failure) => {
+                    self.report(format!(
+                        "❌ {} | {}",
+                        failure.proxy_url(),
+                        failure.error().to_string().red()
+                    ));
+                    failures.push(failure);
+                }
             }
         }
 
+        Self::print_parse_report(&report);
+        self.print_test_stats(&results, &failures);
+
+        if let Some(writer) = &self.writer {
+            writer.write(&results).await?;
+            writer.write_rejects(&report.rejected()).await?;
+            writer.write_failures(&failures).await?;
+            writer.write_socks_probes(&socks_probes).await?;
+        }
+
         Ok(())
     }
+
+    /// Progress and detail lines; stdout unless output is `plain`.
+    fn report(&self, line: impl std::fmt::Display) {
+        if self.options.plain {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    fn print_result(&self, result: &TestResult) {
+        if self.options.plain {
+            for scheme in result.schemes() {
+                println!("{}", result.scheme_url(scheme));
+            }
+        }
+
+        for (i, scheme) in result.schemes().iter().enumerate() {
+            let timings = scheme.timings();
+            self.report(format!(
+                "{} {} | {} | {}ms {} | retries: {} | score: {}",
+                if i == 0 { "✅" } else { "  ↳" },
+                result.scheme_url(scheme),
+                scheme.anonymity(),
+                scheme.latency_ms(),
+                format!(
+                    "(connect {} / handshake {} / tls {} / ttfb {})",
+                    timings.connect_ms(),
+                    timings.handshake_ms(),
+                    timings.tls_ms(),
+                    timings.ttfb_ms()
+                )
+                .dimmed(),
+                scheme.retries(),
+                scheme.score()
+            ));
+
+            if let Some(stats) = scheme.latency_stats() {
+                self.report(format!(
+                    "    {}",
+                    format!(
+                        "samples: {}/{} ok | min {}ms | median {}ms | p95 {}ms | jitter {}ms",
+                        stats.succeeded(),
+                        stats.attempted(),
+                        stats.min_ms(),
+                        stats.median_ms(),
+                        stats.p95_ms(),
+                        stats.jitter_ms()
+                    )
+                    .dimmed()
+                ));
+            }
+
+            if let Some(throughput) = scheme.throughput() {
+                let mut line = format!(
+                    "throughput: ↓ {} ({} bytes)",
+                    format_rate(throughput.download_bps()),
+                    throughput.downloaded()
+                );
+                if let Some(upload) = throughput.upload_bps() {
+                    line.push_str(&format!(" | ↑ {}", format_rate(upload)));
+                }
+                self.report(format!("    {}", line.dimmed()));
+            }
+
+            if !scheme.leaked_headers().is_empty() {
+                let leaks = scheme
+                    .leaked_headers()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.report(format!("    {}", format!("leaks: {}", leaks).dimmed()));
+            }
+        }
+
+        if result.proxy_location().is_some() || result.exit_location().is_some() {
+            let describe = |location: Option<&GeoLocation>| {
+                location.map_or_else(|| "unknown".to_string(), ToString::to_string)
+            };
+            self.report(format!(
+                "    {}",
+                format!(
+                    "location: proxy {} → exit {}",
+                    describe(result.proxy_location()),
+                    describe(result.exit_location())
+                )
+                .dimmed()
+            ));
+        }
+    }
+
+    fn print_test_stats(&self, results: &[TestResult], failures: &[TestFailure]) {
+        if results.is_empty() && failures.is_empty() {
+            return;
+        }
+
+        let mut summary = format!(
+            "📊 Tested {} proxies: {} working, {} failed",
+            results.len() + failures.len(),
+            results.len(),
+            failures.len()
+        );
+
+        if !failures.is_empty() {
+            let by_kind = failures
+                .iter()
+                .fold(BTreeMap::new(), |mut counts, failure| {
+                    *counts.entry(failure.error().kind()).or_insert(0) += 1;
+                    counts
+                })
+                .iter()
+                .map(|(kind, count)| format!("{}: {}", kind, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            summary.push_str(&format!(" ({})", by_kind));
+        }
+
+        self.report(summary);
+    }
+
+    fn print_socks_probe(&self, authority: &str, probe: &Result<SocksProbe, SocksProbeError>) {
+        let probe = match probe {
+            Ok(probe) => probe,
+            Err(e) => {
+                self.report(format!("🧦 {} | {}", authority, e));
+                return;
+            }
+        };
+
+        let target = |reply: Option<SocksReply>| reply.map_or("-".to_string(), |r| r.to_string());
+        let mut line = format!("🧦 {}://{}", probe.version(), authority);
+
+        if !probe.auth_methods().is_empty() {
+            let methods = probe
+                .auth_methods()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            line.push_str(&format!(" | auth: {}", methods));
+        }
+
+        line.push_str(&format!(
+            " | ip target: {} | domain target: {}",
+            target(probe.ip_target()),
+            target(probe.domain_target())
+        ));
+
+        self.report(line);
+    }
+
+    fn print_parse_report(report: &ParseReport) {
+        let rejected = report.rejected();
+        if rejected.is_empty() && report.duplicates() == 0 {
+            return;
+        }
+
+        for r in &rejected {
+            eprintln!(
+                "{} {}",
+                format!("{}:{}:", r.source(), r.line()).dimmed(),
+                r.error()
+            );
+        }
+
+        let mut summary = format!(
+            "⚠️  Parsed {} proxies, rejected {} lines",
+            report.accepted(),
+            rejected.len()
+        );
+
+        if !rejected.is_empty() {
+            let by_kind = report
+                .rejected_by_kind()
+                .iter()
+                .map(|(kind, count)| format!("{}: {}", kind, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            summary.push_str(&format!(" ({})", by_kind));
+        }
+
+        if report.duplicates() > 0 {
+            summary.push_str(&format!(", removed {} duplicates", report.duplicates()));
+        }
+
+        eprintln!("{}", summary);
+    }
+}
+
+fn format_rate(bytes_per_sec: u64) -> String {
+    match bytes_per_sec {
+        b if b >= 1024 * 1024 => format!("{:.1} MB/s", b as f64 / (1024.0 * 1024.0)),
+        b if b >= 1024 => format!("{:.1} KB/s", b as f64 / 1024.0),
+        b => format!("{} B/s", b),
+    }
 }
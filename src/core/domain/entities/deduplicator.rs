@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+use crate::core::domain::{IpAdress, Port, Proxy, ProxyScheme};
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct DedupKey {
+    host: IpAdress,
+    port: Port,
+    scheme: Option<ProxyScheme>,
+}
+
+/// Remembers which proxy endpoints have been seen. A scheme-less entry is tested
+/// with every scheme, so it also covers later entries for the same host and port.
+/// Sources deliver entries in input order, so the outcome is the same on every run.
+#[derive(Debug, Default)]
+pub struct Deduplicator {
+    seen: HashSet<DedupKey>,
+}
+
+impl Deduplicator {
+    /// Returns `false` when the proxy duplicates an earlier one. `host` replaces the
+    /// proxy's own host in the key, e.g. with the address its hostname resolved to.
+    pub fn insert(&mut self, proxy: &Proxy, host: Option<IpAdress>) -> bool {
+        let host = host.unwrap_or_else(|| proxy.ip().clone());
+        let any_scheme = DedupKey {
+            host: host.clone(),
+            port: *proxy.port(),
+            scheme: None,
+        };
+
+        if self.seen.contains(&any_scheme) {
+            return false;
+        }
+
+        self.seen.insert(DedupKey {
+            host,
+            port: *proxy.port(),
+            scheme: proxy.scheme().clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(line: &str) -> Proxy {
+        Proxy::from_str(line).unwrap()
+    }
+
+    #[test]
+    fn scheme_less_entry_covers_later_schemes() {
+        let mut dedup = Deduplicator::default();
+        assert!(dedup.insert(&proxy("1.2.3.4:8080"), None));
+        assert!(!dedup.insert(&proxy("http://1.2.3.4:8080"), None));
+        assert!(!dedup.insert(&proxy("1.2.3.4:8080"), None));
+    }
+
+    #[test]
+    fn different_schemes_are_kept_apart() {
+        let mut dedup = Deduplicator::default();
+        assert!(dedup.insert(&proxy("http://1.2.3.4:8080"), None));
+        assert!(dedup.insert(&proxy("socks5://1.2.3.4:8080"), None));
+        assert!(!dedup.insert(&proxy("socks5://1.2.3.4:8080"), None));
+        assert!(dedup.insert(&proxy("http://1.2.3.4:3128"), None));
+    }
+}
@@ -0,0 +1,38 @@
+use std::fmt;
+
+/// Where an address is registered according to a GeoIP database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeoLocation {
+    country: Option<String>,
+    city: Option<String>,
+}
+
+impl GeoLocation {
+    /// `None` when neither is known.
+    pub fn new(country: Option<String>, city: Option<String>) -> Option<Self> {
+        if country.is_none() && city.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            country: country.map(|c| c.to_uppercase()),
+            city,
+        })
+    }
+
+    /// ISO 3166-1 alpha-2 code, uppercase.
+    pub fn country(&self) -> Option<&str> {
+        self.country.as_deref()
+    }
+}
+
+impl fmt::Display for GeoLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.country, &self.city) {
+            (Some(country), Some(city)) => write!(f, "{} {}", country, city),
+            (Some(country), None) => write!(f, "{}", country),
+            (None, Some(city)) => write!(f, "{}", city),
+            (None, None) => Ok(()),
+        }
+    }
+}
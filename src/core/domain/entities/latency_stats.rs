@@ -0,0 +1,126 @@
+/// Latency spread over several sequential probes of one working scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    attempted: u8,
+    succeeded: u8,
+    min_ms: u128,
+    median_ms: u128,
+    p95_ms: u128,
+    jitter_ms: u128,
+}
+
+impl LatencyStats {
+    /// `latencies` are the successful probes in the order they were taken, out
+    /// of `attempted`; `None` when none succeeded. Jitter is the mean
+    /// difference between consecutive probes.
+    pub fn new(latencies: &[u128], attempted: u8) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+
+        let jitter_ms = if latencies.len() > 1 {
+            latencies
+                .windows(2)
+                .map(|w| w[0].abs_diff(w[1]))
+                .sum::<u128>()
+                / (latencies.len() as u128 - 1)
+        } else {
+            0
+        };
+
+        let mut sorted = latencies.to_vec();
+        sorted.sort_unstable();
+        let len = sorted.len();
+        let median_ms = if len.is_multiple_of(2) {
+            (sorted[len / 2 - 1] + sorted[len / 2]) / 2
+        } else {
+            sorted[len / 2]
+        };
+        // Nearest rank.
+        let p95_ms = sorted[(len * 95).div_ceil(100) - 1];
+
+        Some(Self {
+            attempted,
+            succeeded: len as u8,
+            min_ms: sorted[0],
+            median_ms,
+            p95_ms,
+            jitter_ms,
+        })
+    }
+
+    pub fn attempted(&self) -> u8 {
+        self.attempted
+    }
+
+    pub fn succeeded(&self) -> u8 {
+        self.succeeded
+    }
+
+    pub fn success_ratio(&self) -> f64 {
+        self.succeeded as f64 / self.attempted.max(1) as f64
+    }
+
+    pub fn min_ms(&self) -> u128 {
+        self.min_ms
+    }
+
+    pub fn median_ms(&self) -> u128 {
+        self.median_ms
+    }
+
+    pub fn p95_ms(&self) -> u128 {
+        self.p95_ms
+    }
+
+    pub fn jitter_ms(&self) -> u128 {
+        self.jitter_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_samples_give_no_stats() {
+        assert_eq!(LatencyStats::new(&[], 3), None);
+    }
+
+    #[test]
+    fn single_sample_has_no_jitter() {
+        let stats = LatencyStats::new(&[120], 3).unwrap();
+        assert_eq!(stats.min_ms(), 120);
+        assert_eq!(stats.median_ms(), 120);
+        assert_eq!(stats.p95_ms(), 120);
+        assert_eq!(stats.jitter_ms(), 0);
+        assert_eq!(stats.succeeded(), 1);
+        assert_eq!(stats.attempted(), 3);
+    }
+
+    #[test]
+    fn spread_of_several_samples() {
+        let stats = LatencyStats::new(&[100, 300, 200, 400, 150], 5).unwrap();
+        assert_eq!(stats.min_ms(), 100);
+        assert_eq!(stats.median_ms(), 200);
+        assert_eq!(stats.p95_ms(), 400);
+        // |100-300| + |300-200| + |200-400| + |400-150| = 750 over 4 gaps.
+        assert_eq!(stats.jitter_ms(), 187);
+        assert_eq!(stats.success_ratio(), 1.0);
+    }
+
+    #[test]
+    fn even_sample_count_averages_the_middle_pair() {
+        let stats = LatencyStats::new(&[40, 10, 30, 20], 4).unwrap();
+        assert_eq!(stats.median_ms(), 25);
+        assert_eq!(stats.p95_ms(), 40);
+    }
+
+    #[test]
+    fn p95_uses_nearest_rank() {
+        let latencies: Vec<u128> = (1..=20).collect();
+        let stats = LatencyStats::new(&latencies, 20).unwrap();
+        assert_eq!(stats.p95_ms(), 19);
+        assert_eq!(stats.jitter_ms(), 1);
+    }
+}
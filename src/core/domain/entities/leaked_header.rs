@@ -0,0 +1,47 @@
+use std::fmt;
+
+/// What a header added by the proxy gives away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeakKind {
+    /// Carries the address of the client behind the proxy.
+    ClientIp,
+    /// Only reveals that a proxy is in the path.
+    Proxy,
+}
+
+/// A request header the judge received that the client never sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakedHeader {
+    name: String,
+    value: String,
+    kind: LeakKind,
+}
+
+impl LeakedHeader {
+    pub fn new(name: &str, value: &str, kind: LeakKind) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            value: value.trim().to_string(),
+            kind,
+        }
+    }
+
+    /// Lowercase header name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn kind(&self) -> LeakKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for LeakedHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
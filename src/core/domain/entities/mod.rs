@@ -1,3 +1,14 @@
+pub mod deduplicator;
+pub mod geo_location;
+pub mod latency_stats;
+pub mod leaked_header;
+pub mod parse_report;
+pub mod phase_timings;
 pub mod proxy;
+pub mod rejected_line;
+pub mod scheme_result;
+pub mod socks_probe;
+pub mod test_failure;
 pub mod test_result;
+pub mod throughput;
 pub mod value_objects;
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+
+use crate::core::domain::RejectedLine;
+
+#[derive(Debug, Default)]
+pub struct ParseReport {
+    accepted: usize,
+    rejected: Vec<RejectedLine>,
+    duplicates: usize,
+}
+
+impl ParseReport {
+    pub fn accept(&mut self) {
+        self.accepted += 1;
+    }
+
+    pub fn reject(&mut self, rejected: RejectedLine) {
+        self.rejected.push(rejected);
+    }
+
+    pub fn duplicate(&mut self) {
+        self.duplicates += 1;
+    }
+
+    pub fn accepted(&self) -> usize {
+        self.accepted
+    }
+
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Rejected lines ordered by source and line number.
+    pub fn rejected(&self) -> Vec<&RejectedLine> {
+        let mut rejected: Vec<&RejectedLine> = self.rejected.iter().collect();
+        rejected.sort_by(|a, b| (a.source(), a.line()).cmp(&(b.source(), b.line())));
+        rejected
+    }
+
+    pub fn rejected_by_kind(&self) -> BTreeMap<&'static str, usize> {
+        self.rejected
+            .iter()
+            .fold(BTreeMap::new(), |mut counts, rejected| {
+                *counts.entry(rejected.error().kind()).or_insert(0) += 1;
+                counts
+            })
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// Where the time of a judge request through a proxy went, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhaseTimings {
+    connect_ms: u128,
+    handshake_ms: u128,
+    tls_ms: u128,
+    ttfb_ms: u128,
+    total_ms: u128,
+}
+
+impl PhaseTimings {
+    /// `handshake` covers everything needed to get a channel through the proxy
+    /// (TLS to an HTTPS proxy, CONNECT, SOCKS negotiation), `tls` only the
+    /// handshake with the judge and `ttfb` the wait after the request was sent.
+    pub fn new(
+        connect: Duration,
+        handshake: Duration,
+        tls: Duration,
+        ttfb: Duration,
+        total: Duration,
+    ) -> Self {
+        Self {
+            connect_ms: connect.as_millis(),
+            handshake_ms: handshake.as_millis(),
+            tls_ms: tls.as_millis(),
+            ttfb_ms: ttfb.as_millis(),
+            total_ms: total.as_millis(),
+        }
+    }
+
+    pub fn connect_ms(&self) -> u128 {
+        self.connect_ms
+    }
+
+    pub fn handshake_ms(&self) -> u128 {
+        self.handshake_ms
+    }
+
+    pub fn tls_ms(&self) -> u128 {
+        self.tls_ms
+    }
+
+    pub fn ttfb_ms(&self) -> u128 {
+        self.ttfb_ms
+    }
+
+    pub fn total_ms(&self) -> u128 {
+        self.total_ms
+    }
+}
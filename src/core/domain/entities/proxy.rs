@@ -1,8 +1,9 @@
-use url::Url;
-
-use super::value_objects::{IpAdress, Port};
-use crate::core::domain::Error as DomainError;
-use std::{fmt, hash::Hash};
+use crate::core::domain::{Credentials, Error as DomainError, IpAdress, Port};
+use std::{
+    fmt,
+    hash::Hash,
+    net::{IpAddr, Ipv6Addr},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ProxyScheme {
@@ -13,11 +14,15 @@ pub enum ProxyScheme {
     Socks4,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ProxyAnonymity {
     Elite,
+    /// Forwards a made-up client IP instead of ours.
+    Distorting,
     Anonymous,
     Transparent,
+    /// The judge response did not allow a verdict.
+    Unknown,
 }
 
 #[derive(Debug, Clone)]
@@ -25,13 +30,7 @@ pub struct Proxy {
     ip: IpAdress,
     port: Port,
     scheme: Option<ProxyScheme>,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct ProxyCacheKey {
-    ip: IpAdress,
-    port: Port,
-    scheme: ProxyScheme,
+    credentials: Option<Credentials>,
 }
 
 impl ProxyScheme {
@@ -45,26 +44,10 @@ impl ProxyScheme {
         ]
     }
 
-    pub fn get_test_url(&self) -> Url {
-        match self {
-            Self::Http => Url::parse("http://example.com").unwrap(),
-            _ => Url::parse("https://ipinfo.io/ip").unwrap(),
-        }
+    pub fn is_socks(&self) -> bool {
+        matches!(self, Self::Socks4 | Self::Socks5 | Self::Socks5h)
     }
 
-    pub fn get_header_test_url(&self) -> Url {
-        match self {
-            ProxyScheme::Http => Url::parse("http://httpbin.org/headers").unwrap(),
-            _ => Url::parse("https://httpbin.org/headers").unwrap(),
-        }
-    }
-
-    pub fn get_ip_check_url(&self) -> Url {
-        match self {
-            ProxyScheme::Http => Url::parse("http://httpbin.org/ip").unwrap(),
-            _ => Url::parse("https://httpbin.org/ip").unwrap(),
-        }
-    }
     /// string → enum
     pub fn parse(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
@@ -91,13 +74,28 @@ impl fmt::Display for ProxyScheme {
     }
 }
 
-impl ProxyCacheKey {
-    pub fn new(ip: IpAdress, port: Port, scheme: ProxyScheme) -> Self {
-        Self { ip, port, scheme }
+impl ProxyAnonymity {
+    pub fn get_all_anonymity() -> Vec<ProxyAnonymity> {
+        vec![
+            Self::Elite,
+            Self::Distorting,
+            Self::Anonymous,
+            Self::Transparent,
+            Self::Unknown,
+        ]
     }
+}
 
-    pub fn url(&self) -> String {
-        format!("{}:{}", self.ip.0, self.port.0)
+impl fmt::Display for ProxyAnonymity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Elite => "elite",
+            Self::Distorting => "distorting",
+            Self::Anonymous => "anonymous",
+            Self::Transparent => "transparent",
+            Self::Unknown => "unknown",
+        };
+        write!(f, "{}", s)
     }
 }
 
@@ -114,11 +112,29 @@ impl Proxy {
         &self.scheme
     }
 
-    pub fn new(ip: IpAdress, port: Port, scheme: Option<ProxyScheme>) -> Self {
+    pub fn credentials(&self) -> &Option<Credentials> {
+        &self.credentials
+    }
+
+    pub fn new(
+        ip: IpAdress,
+        port: Port,
+        scheme: Option<ProxyScheme>,
+        credentials: Option<Credentials>,
+    ) -> Self {
         Self {
-            ip: ip,
-            port: port,
-            scheme: scheme,
+            ip,
+            port,
+            scheme,
+            credentials,
+        }
+    }
+
+    /// [user:pass@]host:port
+    pub fn authority(&self) -> String {
+        match &self.credentials {
+            Some(c) => format!("{}:{}@{}:{}", c.username, c.password, self.ip, self.port),
+            None => format!("{}:{}", self.ip, self.port),
         }
     }
 
@@ -134,24 +150,97 @@ impl Proxy {
             remainder = &s[pos + 3..];
         }
 
-        let parts: Vec<&str> = remainder.split(':').collect();
-        if parts.len() != 2 {
-            return Err(DomainError::InvalidIp(s.to_string()));
-        }
+        let mut credentials = None;
+
+        // The host part never contains '@', so the last one separates the userinfo.
+        if let Some((userinfo, host)) = remainder.rsplit_once('@') {
+            let (username, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+            if username.is_empty() {
+                return Err(DomainError::InvalidCredentials(s.to_string()));
+            }
 
-        let ip = parts[0].to_string();
-        if ip.is_empty() {
-            return Err(DomainError::InvalidIp(s.to_string()));
+            credentials = Some(Credentials {
+                username: username.to_string(),
+                password: password.to_string(),
+            });
+            remainder = host;
         }
 
-        let port: u16 = parts[1]
-            .parse()
-            .map_err(|_| DomainError::InvalidPort(s.to_string()))?;
+        let (ip, port) = if let Some(bracketed) = remainder.strip_prefix('[') {
+            // IPv6 literals must be bracketed, otherwise the port separator is ambiguous.
+            let (ip, port) = bracketed
+                .split_once("]:")
+                .ok_or(DomainError::InvalidIp(s.to_string()))?;
+            let ip = ip
+                .parse::<Ipv6Addr>()
+                .map_err(|_| DomainError::InvalidIp(s.to_string()))?;
+            (IpAdress::from(IpAddr::V6(ip)), port)
+        } else {
+            let parts: Vec<&str> = remainder.split(':').collect();
+            if parts.len() != 2 {
+                return Err(DomainError::InvalidIp(s.to_string()));
+            }
+            let ip = IpAdress::new(parts[0]).map_err(|_| DomainError::InvalidIp(s.to_string()))?;
+            (ip, parts[1])
+        };
 
-        if port == 0 || port > 65534 {
-            return Err(DomainError::InvalidPort(s.to_string()));
-        }
+        let port = port
+            .parse::<u16>()
+            .ok()
+            .and_then(|port| Port::new(port).ok())
+            .ok_or(DomainError::InvalidPort(s.to_string()))?;
+
+        Ok(Self::new(ip, port, scheme, credentials))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bracketed_ipv6() {
+        let proxy = Proxy::from_str("socks5://[::1]:1080").unwrap();
+        assert_eq!(proxy.ip().ip(), Some("::1".parse().unwrap()));
+        assert_eq!(proxy.port().to_string(), "1080");
+        assert_eq!(proxy.scheme(), &Some(ProxyScheme::Socks5));
+        assert_eq!(proxy.authority(), "[::1]:1080");
+
+        assert!(Proxy::from_str("::1:1080").is_err());
+        assert!(Proxy::from_str("[::1]").is_err());
+    }
+
+    #[test]
+    fn parses_credentials_with_special_characters() {
+        let proxy = Proxy::from_str("http://user:p@ss:w@rd@[2001:db8::1]:8080").unwrap();
+        let credentials = proxy.credentials().as_ref().unwrap();
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.password, "p@ss:w@rd");
+        assert_eq!(proxy.ip().ip(), Some("2001:db8::1".parse().unwrap()));
+
+        let proxy = Proxy::from_str("user@1.2.3.4:3128").unwrap();
+        let credentials = proxy.credentials().as_ref().unwrap();
+        assert_eq!(credentials.username, "user");
+        assert_eq!(credentials.password, "");
+    }
 
-        Ok(Self::new(IpAdress(ip), Port(port.to_string()), scheme))
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(matches!(
+            Proxy::from_str(":pass@1.2.3.4:80"),
+            Err(DomainError::InvalidCredentials(_))
+        ));
+        assert!(matches!(
+            Proxy::from_str("ftp://1.2.3.4:80"),
+            Err(DomainError::InvalidScheme(_))
+        ));
+        assert!(matches!(
+            Proxy::from_str("1.2.3.4:0"),
+            Err(DomainError::InvalidPort(_))
+        ));
+        assert!(matches!(
+            Proxy::from_str("1.2.3.4"),
+            Err(DomainError::InvalidIp(_))
+        ));
     }
 }
@@ -0,0 +1,38 @@
+use crate::core::domain::Error as DomainError;
+
+/// An input line that could not be parsed into a proxy.
+#[derive(Debug, Clone)]
+pub struct RejectedLine {
+    source: String,
+    line: usize,
+    raw: String,
+    error: DomainError,
+}
+
+impl RejectedLine {
+    pub fn new(source: &str, line: usize, raw: &str, error: DomainError) -> Self {
+        Self {
+            source: source.to_string(),
+            line,
+            raw: raw.trim().to_string(),
+            error,
+        }
+    }
+
+    /// File path, URL or other origin the line was read from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn error(&self) -> &DomainError {
+        &self.error
+    }
+}
@@ -0,0 +1,104 @@
+use std::net::IpAddr;
+
+use crate::core::domain::{
+    LatencyStats, LeakedHeader, PhaseTimings, ProxyAnonymity, ProxyScheme, Throughput,
+};
+
+/// Outcome of testing a proxy over one scheme.
+#[derive(Debug, Clone)]
+pub struct SchemeResult {
+    scheme: ProxyScheme,
+    timings: PhaseTimings,
+    retries: u8,
+    anonymity: ProxyAnonymity,
+    exit_ip: Option<IpAddr>,
+    leaked_headers: Vec<LeakedHeader>,
+    latency_stats: Option<LatencyStats>,
+    throughput: Option<Throughput>,
+    score: u8,
+}
+
+impl SchemeResult {
+    pub fn new(
+        scheme: ProxyScheme,
+        timings: PhaseTimings,
+        retries: u8,
+        anonymity: ProxyAnonymity,
+        exit_ip: Option<IpAddr>,
+        leaked_headers: Vec<LeakedHeader>,
+        score: u8,
+    ) -> Self {
+        Self {
+            scheme,
+            timings,
+            retries,
+            anonymity,
+            exit_ip,
+            leaked_headers,
+            latency_stats: None,
+            throughput: None,
+            score,
+        }
+    }
+
+    /// Attaches what was measured after the scheme was found working and the
+    /// score it led to.
+    pub fn with_measurements(
+        self,
+        latency_stats: Option<LatencyStats>,
+        throughput: Option<Throughput>,
+        score: u8,
+    ) -> Self {
+        Self {
+            latency_stats,
+            throughput,
+            score,
+            ..self
+        }
+    }
+
+    pub fn scheme(&self) -> &ProxyScheme {
+        &self.scheme
+    }
+
+    /// Total time of the judge request, real-IP lookup excluded.
+    pub fn latency_ms(&self) -> u128 {
+        self.timings.total_ms()
+    }
+
+    pub fn timings(&self) -> &PhaseTimings {
+        &self.timings
+    }
+
+    pub fn retries(&self) -> u8 {
+        self.retries
+    }
+
+    pub fn anonymity(&self) -> &ProxyAnonymity {
+        &self.anonymity
+    }
+
+    /// Address the judge saw the request come from, if it told us.
+    pub fn exit_ip(&self) -> Option<IpAddr> {
+        self.exit_ip
+    }
+
+    /// Revealing headers the proxy added to the judge request.
+    pub fn leaked_headers(&self) -> &[LeakedHeader] {
+        &self.leaked_headers
+    }
+
+    /// Present when the scheme was probed more than once.
+    pub fn latency_stats(&self) -> Option<&LatencyStats> {
+        self.latency_stats.as_ref()
+    }
+
+    /// Present when the throughput stage ran and the download succeeded.
+    pub fn throughput(&self) -> Option<&Throughput> {
+        self.throughput.as_ref()
+    }
+
+    pub fn score(&self) -> u8 {
+        self.score
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+
+use crate::core::domain::error::ProxyTestError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocksVersion {
+    Socks4,
+    Socks5,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocksAuthMethod {
+    NoAuth,
+    UsernamePassword,
+}
+
+/// How the server answered a CONNECT request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocksReply {
+    Granted,
+    /// Raw reply code, `0x01`-`0x08` for SOCKS5 and `0x5B`-`0x5D` for SOCKS4.
+    Refused(u8),
+    /// A reply code outside the ones defined by the protocol.
+    Unknown,
+    /// The server closed the connection or stayed silent after the request.
+    NoReply,
+    /// None of the offered auth methods fits our credentials.
+    AuthRequired,
+    /// RFC 1929 status of a rejected username/password.
+    AuthFailed(u8),
+}
+
+impl SocksReply {
+    /// The test failure this reply stands for; `None` when the request went
+    /// through or the server never answered it.
+    pub fn failure(&self) -> Option<ProxyTestError> {
+        match self {
+            Self::Granted | Self::NoReply => None,
+            Self::AuthRequired | Self::AuthFailed(_) | Self::Refused(0x5C | 0x5D) => {
+                Some(ProxyTestError::AuthRequired)
+            }
+            Self::Refused(0x03..=0x06) => Some(ProxyTestError::JudgeUnreachable),
+            Self::Refused(_) | Self::Unknown => {
+                Some(ProxyTestError::ProxyRejected(format!("SOCKS {}", self)))
+            }
+        }
+    }
+}
+
+/// Outcome of a native SOCKS handshake against a proxy.
+#[derive(Debug, Clone)]
+pub struct SocksProbe {
+    version: SocksVersion,
+    auth_methods: Vec<SocksAuthMethod>,
+    ip_target: Option<SocksReply>,
+    domain_target: Option<SocksReply>,
+}
+
+impl SocksProbe {
+    /// `None` targets were not tried, e.g. the judge host did not resolve to IPv4
+    /// for SOCKS4 or was already an IP literal.
+    pub fn new(
+        version: SocksVersion,
+        auth_methods: Vec<SocksAuthMethod>,
+        ip_target: Option<SocksReply>,
+        domain_target: Option<SocksReply>,
+    ) -> Self {
+        Self {
+            version,
+            auth_methods,
+            ip_target,
+            domain_target,
+        }
+    }
+
+    pub fn version(&self) -> SocksVersion {
+        self.version
+    }
+
+    /// Methods the server accepted when offered alone; always empty for SOCKS4.
+    pub fn auth_methods(&self) -> &[SocksAuthMethod] {
+        &self.auth_methods
+    }
+
+    pub fn ip_target(&self) -> Option<SocksReply> {
+        self.ip_target
+    }
+
+    /// Reply to a CONNECT by hostname (socks5h / SOCKS4a).
+    pub fn domain_target(&self) -> Option<SocksReply> {
+        self.domain_target
+    }
+
+    /// Why the proxy cannot be used, when it turned down every target tried.
+    pub fn failure(&self) -> Option<ProxyTestError> {
+        let replies = [self.ip_target, self.domain_target];
+        if replies.contains(&Some(SocksReply::Granted)) {
+            return None;
+        }
+
+        replies.iter().flatten().find_map(SocksReply::failure)
+    }
+}
+
+impl fmt::Display for SocksVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Socks4 => "socks4",
+            Self::Socks5 => "socks5",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Display for SocksAuthMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::NoAuth => "none",
+            Self::UsernamePassword => "username/password",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Display for SocksReply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Granted => write!(f, "granted"),
+            Self::Refused(code) => {
+                let reason = match code {
+                    0x01 => "general failure",
+                    0x02 => "not allowed by ruleset",
+                    0x03 => "network unreachable",
+                    0x04 => "host unreachable",
+                    0x05 => "connection refused",
+                    0x06 => "TTL expired",
+                    0x07 => "command not supported",
+                    0x08 => "address type not supported",
+                    0x5B => "rejected or failed",
+                    0x5C => "identd unreachable",
+                    0x5D => "identd user mismatch",
+                    _ => "unknown",
+                };
+                write!(f, "refused 0x{:02X} ({})", code, reason)
+            }
+            Self::Unknown => write!(f, "unknown reply code"),
+            Self::NoReply => write!(f, "no reply"),
+            Self::AuthRequired => write!(f, "authentication required"),
+            Self::AuthFailed(status) => write!(f, "authentication failed 0x{:02X}", status),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::core::domain::{GeoLocation, Proxy, error::ProxyTestError};
+
+/// A proxy that could not be used, with the reason it failed.
+#[derive(Debug, Clone)]
+pub struct TestFailure {
+    proxy: Proxy,
+    error: ProxyTestError,
+    location: Option<GeoLocation>,
+}
+
+impl TestFailure {
+    /// `location` is where the proxy's own address is, when looked up.
+    pub fn new(proxy: Proxy, error: ProxyTestError, location: Option<GeoLocation>) -> Self {
+        Self {
+            proxy,
+            error,
+            location,
+        }
+    }
+
+    pub fn country(&self) -> Option<&str> {
+        self.location.as_ref().and_then(GeoLocation::country)
+    }
+
+    pub fn error(&self) -> &ProxyTestError {
+        &self.error
+    }
+
+    /// The proxy as given, without a scheme if none was specified.
+    pub fn proxy_url(&self) -> String {
+        match self.proxy.scheme() {
+            Some(scheme) => format!("{}://{}", scheme, self.proxy.authority()),
+            None => self.proxy.authority(),
+        }
+    }
+}
@@ -1,34 +1,88 @@
-use crate::core::domain::{IpAdress, Port, ProxyAnonymity, ProxyScheme};
+use std::cmp::Reverse;
+
+use crate::core::domain::{GeoLocation, Proxy, SchemeResult};
 
 #[derive(Debug)]
 pub struct TestResult {
-    ip: IpAdress,
-    port: Port,
-    scheme: ProxyScheme,
-    latency_ms: u128,
-    retries: u8,
-    anonymity: ProxyAnonymity,
-    score: u8,
+    proxy: Proxy,
+    /// Never empty, best first.
+    schemes: Vec<SchemeResult>,
+    proxy_location: Option<GeoLocation>,
+    exit_location: Option<GeoLocation>,
 }
 
 impl TestResult {
-    pub fn new(
-        ip: IpAdress,
-        port: Port,
-        scheme: ProxyScheme,
-        latency_ms: u128,
-        retries: u8,
-        anonymity: ProxyAnonymity,
-        score: u8,
-    ) -> Self {
-        Self {
-            ip,
-            port,
-            scheme,
-            latency_ms,
-            retries,
-            anonymity,
-            score,
+    /// Orders `schemes` by score, ties going to the lower latency; `None` when
+    /// no scheme worked.
+    pub fn new(proxy: Proxy, mut schemes: Vec<SchemeResult>) -> Option<Self> {
+        if schemes.is_empty() {
+            return None;
         }
+
+        schemes.sort_by_key(|s| Reverse((s.score(), Reverse(s.latency_ms()))));
+        Some(Self {
+            proxy,
+            schemes,
+            proxy_location: None,
+            exit_location: None,
+        })
+    }
+
+    /// Where the proxy address and the exit IP of the best scheme are located.
+    pub fn set_locations(
+        &mut self,
+        proxy_location: Option<GeoLocation>,
+        exit_location: Option<GeoLocation>,
+    ) {
+        self.proxy_location = proxy_location;
+        self.exit_location = exit_location;
+    }
+
+    /// Drops every scheme but the best one.
+    pub fn keep_best(&mut self) {
+        self.schemes.truncate(1);
+    }
+
+    /// Hands the schemes back, e.g. to rescore them and build a new result.
+    pub fn into_parts(self) -> (Proxy, Vec<SchemeResult>) {
+        (self.proxy, self.schemes)
+    }
+
+    pub fn best(&self) -> &SchemeResult {
+        &self.schemes[0]
+    }
+
+    /// Every scheme that worked, best first.
+    pub fn schemes(&self) -> &[SchemeResult] {
+        &self.schemes
+    }
+
+    pub fn proxy_location(&self) -> Option<&GeoLocation> {
+        self.proxy_location.as_ref()
+    }
+
+    pub fn exit_location(&self) -> Option<&GeoLocation> {
+        self.exit_location.as_ref()
+    }
+
+    /// Country traffic appears to come from: the exit's, else the proxy's.
+    pub fn country(&self) -> Option<&str> {
+        self.exit_location
+            .as_ref()
+            .and_then(GeoLocation::country)
+            .or_else(|| self.proxy_location.as_ref().and_then(GeoLocation::country))
+    }
+
+    pub fn score(&self) -> u8 {
+        self.best().score()
+    }
+
+    /// scheme://[user:pass@]host:port of the best scheme
+    pub fn proxy_url(&self) -> String {
+        self.scheme_url(self.best())
+    }
+
+    pub fn scheme_url(&self, scheme: &SchemeResult) -> String {
+        format!("{}://{}", scheme.scheme(), self.proxy.authority())
     }
 }
@@ -0,0 +1,31 @@
+/// Transfer rates measured through a proxy, in bytes per second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Throughput {
+    downloaded: u64,
+    download_bps: u64,
+    upload_bps: Option<u64>,
+}
+
+impl Throughput {
+    pub fn new(downloaded: u64, download_bps: u64, upload_bps: Option<u64>) -> Self {
+        Self {
+            downloaded,
+            download_bps,
+            upload_bps,
+        }
+    }
+
+    /// Bytes received, less than the payload if the time limit cut it short.
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded
+    }
+
+    pub fn download_bps(&self) -> u64 {
+        self.download_bps
+    }
+
+    /// Present when an upload was asked for and succeeded.
+    pub fn upload_bps(&self) -> Option<u64> {
+        self.upload_bps
+    }
+}
@@ -1,7 +1,115 @@
-use std::hash::Hash;
+use crate::core::domain::Error as DomainError;
+use std::{
+    fmt,
+    hash::Hash,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct IpAdress(pub String);
+pub enum Host {
+    V4(Ipv4Addr),
+    V6(Ipv6Addr),
+    Hostname(String),
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Port(pub String);
+pub struct IpAdress(Host);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Port(u16);
+
+impl IpAdress {
+    /// Accepts an IPv4 literal, an unbracketed IPv6 literal or a DNS hostname.
+    pub fn new(s: &str) -> Result<Self, DomainError> {
+        if let Ok(ip) = s.parse::<IpAddr>() {
+            return Ok(Self::from(ip));
+        }
+
+        if Self::is_valid_hostname(s) {
+            return Ok(Self(Host::Hostname(s.to_ascii_lowercase())));
+        }
+
+        Err(DomainError::InvalidIp(s.to_string()))
+    }
+
+    /// The address itself, unless it is a hostname.
+    pub fn ip(&self) -> Option<IpAddr> {
+        match &self.0 {
+            Host::V4(ip) => Some(IpAddr::V4(*ip)),
+            Host::V6(ip) => Some(IpAddr::V6(*ip)),
+            Host::Hostname(_) => None,
+        }
+    }
+
+    pub fn hostname(&self) -> Option<&str> {
+        match &self.0 {
+            Host::Hostname(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// RFC 1123 labels, at least two of them (or `localhost`), and a non-numeric
+    /// top-level label so malformed IPv4 such as `1.2.3.999` is not taken for a name.
+    fn is_valid_hostname(s: &str) -> bool {
+        let s = s.strip_suffix('.').unwrap_or(s);
+
+        if s.eq_ignore_ascii_case("localhost") {
+            return true;
+        }
+
+        if s.is_empty() || s.len() > 253 {
+            return false;
+        }
+
+        let labels: Vec<&str> = s.split('.').collect();
+        let valid_labels = labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+
+        let tld_is_numeric = labels
+            .last()
+            .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()));
+
+        valid_labels && labels.len() >= 2 && !tld_is_numeric
+    }
+}
+
+impl From<IpAddr> for IpAdress {
+    fn from(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(ip) => Self(Host::V4(ip)),
+            IpAddr::V6(ip) => Self(Host::V6(ip)),
+        }
+    }
+}
+
+impl fmt::Display for IpAdress {
+    /// IPv6 literals are bracketed so the result can be joined with a port.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Host::V4(ip) => write!(f, "{}", ip),
+            Host::V6(ip) => write!(f, "[{}]", ip),
+            Host::Hostname(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Port {
+    pub fn new(port: u16) -> Result<Self, DomainError> {
+        if port == 0 {
+            return Err(DomainError::InvalidPort(port.to_string()));
+        }
+
+        Ok(Self(port))
+    }
+}
+
+impl fmt::Display for Port {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
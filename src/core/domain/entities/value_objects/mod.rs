@@ -1,3 +1,5 @@
+mod credentials;
 mod ip_adress;
 
+pub use credentials::Credentials;
 pub use ip_adress::{IpAdress, Port};
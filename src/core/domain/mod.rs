@@ -1,5 +1,9 @@
 mod entities;
 pub mod error;
 
-pub use entities::{proxy::*, test_result::*, value_objects::*};
+pub use entities::{
+    deduplicator::*, geo_location::*, latency_stats::*, leaked_header::*, parse_report::*,
+    phase_timings::*, proxy::*, rejected_line::*, scheme_result::*, socks_probe::*,
+    test_failure::*, test_result::*, throughput::*, value_objects::*,
+};
 pub use error::Error;
@@ -0,0 +1,44 @@
+use crate::core::application::{Error as AppError, ports::ProxyRepository};
+use crate::core::domain::{Proxy, RejectedLine};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Merges several sources into a single stream. Every source starts reading at
+/// once, but their entries are passed on one source after another, in the order
+/// the sources were given.
+pub struct CompositeProxyRepository {
+    repos: Vec<Arc<dyn ProxyRepository>>,
+}
+
+impl CompositeProxyRepository {
+    pub fn new(repos: Vec<Arc<dyn ProxyRepository>>) -> Self {
+        Self { repos }
+    }
+}
+
+#[async_trait]
+impl ProxyRepository for CompositeProxyRepository {
+    async fn stream_proxies(
+        &self,
+    ) -> Result<mpsc::Receiver<Result<Proxy, RejectedLine>>, AppError> {
+        let (tx, rx) = mpsc::channel::<Result<Proxy, RejectedLine>>(100);
+
+        let mut sources = Vec::with_capacity(self.repos.len());
+        for repo in &self.repos {
+            sources.push(repo.stream_proxies().await?);
+        }
+
+        tokio::spawn(async move {
+            for mut source_rx in sources {
+                while let Some(parsed) = source_rx.recv().await {
+                    if tx.send(parsed).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
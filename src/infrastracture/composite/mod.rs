@@ -0,0 +1,3 @@
+mod composite_proxy_repository;
+
+pub use composite_proxy_repository::CompositeProxyRepository;
@@ -0,0 +1,3 @@
+mod tokio_host_resolver;
+
+pub use tokio_host_resolver::TokioHostResolver;
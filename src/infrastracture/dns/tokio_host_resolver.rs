@@ -0,0 +1,44 @@
+use crate::core::application::ports::HostResolver;
+use crate::core::domain::IpAdress;
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::lookup_host;
+use tokio::sync::OnceCell;
+use tokio::time::timeout;
+
+/// Each hostname is looked up once; concurrent requests for it share the lookup.
+pub struct TokioHostResolver {
+    timeout: Duration,
+    cache: DashMap<String, Arc<OnceCell<Option<IpAdress>>>>,
+}
+
+impl TokioHostResolver {
+    pub fn new(timeout: u8) -> Self {
+        Self {
+            timeout: Duration::from_secs(timeout as u64),
+            cache: DashMap::new(),
+        }
+    }
+
+    async fn lookup(&self, name: &str) -> Option<IpAdress> {
+        let mut addrs = timeout(self.timeout, lookup_host((name, 0)))
+            .await
+            .ok()?
+            .ok()?;
+
+        addrs.next().map(|addr| IpAdress::from(addr.ip()))
+    }
+}
+
+#[async_trait]
+impl HostResolver for TokioHostResolver {
+    async fn resolve(&self, host: &IpAdress) -> Option<IpAdress> {
+        let name = host.hostname()?.to_lowercase();
+        // Cloned out so the map is not locked across the lookup.
+        let cell = self.cache.entry(name.clone()).or_default().clone();
+
+        cell.get_or_init(|| self.lookup(&name)).await.clone()
+    }
+}
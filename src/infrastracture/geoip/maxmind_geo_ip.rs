@@ -0,0 +1,41 @@
+use std::net::IpAddr;
+use std::path::Path;
+
+use maxminddb::{Reader, geoip2};
+
+use crate::core::application::{Error as AppError, ports::GeoIpPort};
+use crate::core::domain::GeoLocation;
+
+/// Offline lookups in a MaxMind-format (mmdb) database; GeoLite2/GeoIP2
+/// Country and City databases both work, the former without cities.
+pub struct MaxMindGeoIp {
+    reader: Reader<Vec<u8>>,
+}
+
+impl MaxMindGeoIp {
+    pub fn open(path: &Path) -> Result<Self, AppError> {
+        let reader = Reader::open_readfile(path).map_err(|e| {
+            AppError::External(format!(
+                "Failed to open GeoIP database '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self { reader })
+    }
+}
+
+impl GeoIpPort for MaxMindGeoIp {
+    fn locate(&self, ip: IpAddr) -> Option<GeoLocation> {
+        let record: geoip2::City = self.reader.lookup(ip).ok()?;
+
+        let country = record.country.and_then(|c| c.iso_code).map(str::to_string);
+        let city = record
+            .city
+            .and_then(|c| c.names)
+            .and_then(|names| names.get("en").map(|name| name.to_string()));
+
+        GeoLocation::new(country, city)
+    }
+}
@@ -0,0 +1,3 @@
+mod maxmind_geo_ip;
+
+pub use maxmind_geo_ip::MaxMindGeoIp;
@@ -0,0 +1,424 @@
+use crate::core::application::Error as AppError;
+use serde_json::{Map, Value, json};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time::timeout;
+use tokio_rustls::{TlsAcceptor, rustls::ServerConfig};
+use url::Url;
+
+const MAX_HEAD_SIZE: usize = 16 * 1024;
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Largest `/bytes/N` download and `/post` upload served.
+const MAX_PAYLOAD_SIZE: u64 = 100 * 1024 * 1024;
+static PAYLOAD_CHUNK: [u8; 64 * 1024] = [0u8; 64 * 1024];
+
+/// Minimal HTTP/1.1 echo service answering the probes the tester sends.
+///
+/// `/` returns the client IP and headers as httpbin JSON, `/ip` and `/headers`
+/// return one of them, `/azenv` dumps both in the azenv text format.
+/// `/bytes/N` and `POST /post` serve throughput tests like httpbin's.
+pub struct JudgeServer {
+    listen: SocketAddr,
+    tls: Option<(SocketAddr, Arc<ServerConfig>)>,
+}
+
+impl JudgeServer {
+    pub fn new(listen: SocketAddr, tls: Option<(SocketAddr, Arc<ServerConfig>)>) -> Self {
+        Self { listen, tls }
+    }
+
+    /// Serves until the process is stopped; only binding can fail.
+    pub async fn run(&self) -> Result<(), AppError> {
+        let plain = Self::bind(self.listen).await?;
+        println!("⚖️  Judge listening on http://{}", self.listen);
+
+        let tls = match &self.tls {
+            Some((addr, config)) => {
+                let listener = Self::bind(*addr).await?;
+                println!("⚖️  Judge listening on https://{}", addr);
+                Some((listener, TlsAcceptor::from(config.clone())))
+            }
+            None => None,
+        };
+
+        let serve_tls = async {
+            match tls {
+                Some((listener, acceptor)) => Self::accept_loop(listener, Some(acceptor)).await,
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::join!(Self::accept_loop(plain, None), serve_tls);
+        Ok(())
+    }
+
+    async fn bind(addr: SocketAddr) -> Result<TcpListener, AppError> {
+        TcpListener::bind(addr)
+            .await
+            .map_err(|e| AppError::External(format!("Failed to listen on {}: {}", addr, e)))
+    }
+
+    async fn accept_loop(listener: TcpListener, acceptor: Option<TlsAcceptor>) {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    // Usually transient (e.g. too many open files), keep serving.
+                    eprintln!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let acceptor = acceptor.clone();
+            // Dual-stack listeners report IPv4 clients as ::ffff:a.b.c.d.
+            let peer = peer.ip().to_canonical();
+
+            tokio::spawn(async move {
+                let _ = match acceptor {
+                    Some(acceptor) => match timeout(READ_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => serve(stream, peer).await,
+                        _ => return,
+                    },
+                    None => serve(stream, peer).await,
+                };
+            });
+        }
+    }
+}
+
+async fn serve<S>(mut stream: S, peer: IpAddr) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (head, body_start) = match timeout(READ_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Ok(Some(read))) => read,
+        _ => return Ok(()),
+    };
+    let request = Request::parse(&head);
+    let path = request.path();
+
+    if let Some(size) = path.strip_prefix("/bytes/") {
+        let size = size.parse::<u64>().unwrap_or(0).min(MAX_PAYLOAD_SIZE);
+        stream.write_all(&payload_head(size)).await?;
+        write_payload(&mut stream, size).await?;
+    } else if path == "/post" && request.method == "POST" {
+        let length = request
+            .header("content-length")
+            .and_then(|len| len.parse::<u64>().ok())
+            .unwrap_or(0)
+            .min(MAX_PAYLOAD_SIZE);
+        let received = drain_body(&mut stream, length, body_start.len() as u64).await?;
+        stream
+            .write_all(&response(
+                "200 OK",
+                "application/json",
+                &json!({ "received": received }).to_string(),
+            ))
+            .await?;
+    } else {
+        stream.write_all(&respond(&request, peer)).await?;
+    }
+
+    stream.shutdown().await
+}
+
+/// Reads up to the blank line ending the request head and returns it with
+/// whatever part of the body arrived along with it.
+async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<Option<(String, Vec<u8>)>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    let end = loop {
+        if let Some(end) = head.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        let read = stream.read(&mut buf).await?;
+        if read == 0 || head.len() + read > MAX_HEAD_SIZE {
+            return Ok(None);
+        }
+        head.extend_from_slice(&buf[..read]);
+    };
+
+    let body = head.split_off(end);
+    Ok(Some((String::from_utf8_lossy(&head).into_owned(), body)))
+}
+
+struct Request<'a> {
+    method: &'a str,
+    target: &'a str,
+    headers: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Request<'a> {
+    fn parse(head: &'a str) -> Self {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+
+        Self {
+            method: request_line.next().unwrap_or_default(),
+            target: request_line.next().unwrap_or_default(),
+            headers: lines
+                .take_while(|line| !line.is_empty())
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim(), value.trim()))
+                .collect(),
+        }
+    }
+
+    /// Plain HTTP proxies may forward the absolute-form target unchanged.
+    fn path(&self) -> String {
+        Url::parse(self.target)
+            .map(|url| url.path().to_string())
+            .unwrap_or_else(|_| {
+                self.target
+                    .split('?')
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+    }
+
+    fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    }
+}
+
+fn payload_head(size: u64) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        size
+    )
+    .into_bytes()
+}
+
+async fn write_payload<S: AsyncWrite + Unpin>(stream: &mut S, mut size: u64) -> io::Result<()> {
+    while size > 0 {
+        let len = size.min(PAYLOAD_CHUNK.len() as u64) as usize;
+        stream.write_all(&PAYLOAD_CHUNK[..len]).await?;
+        size -= len as u64;
+    }
+    Ok(())
+}
+
+/// Discards the rest of a `length`-byte body of which `received` bytes were
+/// already read; returns how many bytes arrived in total.
+async fn drain_body<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    length: u64,
+    mut received: u64,
+) -> io::Result<u64> {
+    let mut buf = [0u8; 64 * 1024];
+
+    while received < length {
+        match timeout(READ_TIMEOUT, stream.read(&mut buf)).await {
+            Ok(Ok(0)) | Err(_) => break,
+            Ok(Ok(read)) => received += read as u64,
+            Ok(Err(e)) => return Err(e),
+        }
+    }
+
+    Ok(received.min(length))
+}
+
+fn respond(request: &Request, peer: IpAddr) -> Vec<u8> {
+    let Request {
+        method,
+        target,
+        headers,
+    } = request;
+    let path = request.path();
+
+    let origin = peer.to_string();
+    let (status, content_type, body) = match path.as_str() {
+        "/" => (
+            "200 OK",
+            "application/json",
+            json!({ "origin": origin, "headers": headers_json(headers) }).to_string(),
+        ),
+        "/ip" => (
+            "200 OK",
+            "application/json",
+            json!({ "origin": origin }).to_string(),
+        ),
+        "/headers" => (
+            "200 OK",
+            "application/json",
+            json!({ "headers": headers_json(headers) }).to_string(),
+        ),
+        "/azenv" | "/azenv.php" => (
+            "200 OK",
+            "text/plain",
+            azenv(&origin, method, target, headers),
+        ),
+        _ => ("404 Not Found", "text/plain", "Not Found".to_string()),
+    };
+
+    response(status, content_type, &body)
+}
+
+fn response(status: &str, content_type: &str, body: &str) -> Vec<u8> {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+    .into_bytes()
+}
+
+/// Repeated headers are joined with ", " like httpbin does.
+fn headers_json(headers: &[(&str, &str)]) -> Value {
+    let mut map = Map::new();
+
+    for (name, value) in headers {
+        let joined = match map.get(*name).and_then(Value::as_str) {
+            Some(previous) => format!("{}, {}", previous, value),
+            None => value.to_string(),
+        };
+        map.insert(name.to_string(), Value::String(joined));
+    }
+
+    Value::Object(map)
+}
+
+fn azenv(origin: &str, method: &str, target: &str, headers: &[(&str, &str)]) -> String {
+    let mut body = format!(
+        "REMOTE_ADDR = {}\nREQUEST_METHOD = {}\nREQUEST_URI = {}\n",
+        origin, method, target
+    );
+
+    for (name, value) in headers {
+        body.push_str(&format!(
+            "HTTP_{} = {}\n",
+            name.to_uppercase().replace('-', "_"),
+            value
+        ));
+    }
+
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::application::dto::{BackoffStrategy, JudgeFormat, JudgeUrl};
+    use crate::core::application::ports::ProxyTestPort;
+    use crate::core::domain::{Proxy, ProxyAnonymity};
+    use crate::infrastracture::proxy_test::{
+        JudgeConfig, RealIpResolver, ReqwestProxyTestService, RetryPolicy, TestPlan,
+        ThroughputProbe,
+    };
+
+    const HEAD: &str = "GET http://judge.local/?x=1 HTTP/1.1\r\nHost: judge.local\r\nX-Forwarded-For:  10.0.0.1 \r\nAccept: a\r\naccept: b\r\n\r\n";
+
+    fn body(response: &[u8]) -> String {
+        let response = String::from_utf8_lossy(response);
+        response.split_once("\r\n\r\n").unwrap().1.to_string()
+    }
+
+    #[test]
+    fn parses_request_line_and_headers() {
+        let request = Request::parse(HEAD);
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.target, "http://judge.local/?x=1");
+        assert_eq!(request.path(), "/");
+        assert_eq!(request.headers.len(), 4);
+        assert_eq!(request.header("x-forwarded-for"), Some("10.0.0.1"));
+        assert_eq!(request.header("Missing"), None);
+
+        assert_eq!(
+            Request::parse("GET /bytes/10?a=b HTTP/1.1\r\n\r\n").path(),
+            "/bytes/10"
+        );
+        assert_eq!(Request::parse("").path(), "");
+    }
+
+    #[test]
+    fn echoes_origin_and_joined_headers_as_json() {
+        let request = Request::parse(HEAD);
+        let peer: IpAddr = "192.0.2.9".parse().unwrap();
+
+        let root: Value = serde_json::from_str(&body(&respond(&request, peer))).unwrap();
+        assert_eq!(root["origin"], "192.0.2.9");
+        assert_eq!(root["headers"]["X-Forwarded-For"], "10.0.0.1");
+        assert_eq!(root["headers"]["Accept"], "a");
+        assert_eq!(root["headers"]["accept"], "b");
+
+        let repeated = headers_json(&[("Via", "1.1 a"), ("Via", "1.1 b")]);
+        assert_eq!(repeated, json!({ "Via": "1.1 a, 1.1 b" }));
+
+        let ip = Request::parse("GET /ip HTTP/1.1\r\nVia: 1.1 a\r\n\r\n");
+        assert_eq!(body(&respond(&ip, peer)), r#"{"origin":"192.0.2.9"}"#);
+
+        let unknown = Request::parse("GET /nope HTTP/1.1\r\n\r\n");
+        assert!(respond(&unknown, peer).starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn dumps_azenv_variables() {
+        assert_eq!(
+            azenv("192.0.2.9", "GET", "/azenv", &[("X-Real-IP", "10.0.0.1")]),
+            "REMOTE_ADDR = 192.0.2.9\nREQUEST_METHOD = GET\nREQUEST_URI = /azenv\nHTTP_X_REAL_IP = 10.0.0.1\n"
+        );
+    }
+
+    /// The judge also answers absolute-form requests, so it can stand in for
+    /// a plain HTTP proxy that forwards straight to itself.
+    #[tokio::test]
+    async fn tester_gets_an_elite_result_from_the_judge() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(JudgeServer::accept_loop(listener, None));
+
+        let judge_url = Url::parse(&format!("http://{}/", addr)).unwrap();
+        let judge = JudgeConfig::new(
+            &[JudgeUrl {
+                scheme: None,
+                url: judge_url.clone(),
+            }],
+            &[],
+            Vec::new(),
+            JudgeFormat::Auto,
+            false,
+        );
+        let throughput = ThroughputProbe::new(
+            4096,
+            judge_url.join("/bytes/4096").unwrap(),
+            Some(judge_url.join("/post").unwrap()),
+            None,
+            false,
+        );
+        let tester = ReqwestProxyTestService::new(
+            5,
+            judge.clone(),
+            RealIpResolver::new(vec!["203.0.113.7".parse().unwrap()], &judge, 5),
+            RetryPolicy::new(
+                1,
+                Duration::ZERO,
+                BackoffStrategy::Constant,
+                false,
+                Vec::new(),
+            ),
+            TestPlan::new(10, 1, false, 1),
+            Some(throughput),
+        )
+        .unwrap();
+
+        let proxy = Proxy::from_str(&format!("http://{}", addr)).unwrap();
+        let result = tester.test(proxy).await.unwrap();
+        let best = result.best();
+
+        assert_eq!(*best.anonymity(), ProxyAnonymity::Elite);
+        assert_eq!(best.exit_ip(), Some("127.0.0.1".parse().unwrap()));
+        let throughput = best.throughput().unwrap();
+        assert_eq!(throughput.downloaded(), 4096);
+        assert!(throughput.upload_bps().is_some());
+    }
+}
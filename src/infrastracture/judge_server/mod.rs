@@ -0,0 +1,5 @@
+mod http_judge_server;
+mod tls;
+
+pub use http_judge_server::JudgeServer;
+pub use tls::load_tls_config;
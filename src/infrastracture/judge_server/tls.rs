@@ -0,0 +1,51 @@
+use crate::core::application::Error as AppError;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::{
+    ServerConfig,
+    crypto::aws_lc_rs,
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, pem::PemObject},
+};
+
+/// Loads `cert`/`key` from PEM files, or issues a self-signed certificate for
+/// `names` when none are given.
+pub fn load_tls_config(
+    cert: Option<(&Path, &Path)>,
+    names: &[String],
+) -> Result<Arc<ServerConfig>, AppError> {
+    let (chain, key) = match cert {
+        Some((cert, key)) => {
+            let chain = CertificateDer::pem_file_iter(cert)
+                .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                .map_err(|e| {
+                    AppError::External(format!(
+                        "Failed to read certificate '{}': {}",
+                        cert.display(),
+                        e
+                    ))
+                })?;
+            let key = PrivateKeyDer::from_pem_file(key).map_err(|e| {
+                AppError::External(format!(
+                    "Failed to read private key '{}': {}",
+                    key.display(),
+                    e
+                ))
+            })?;
+            (chain, key)
+        }
+        None => {
+            let certified = rcgen::generate_simple_self_signed(names.to_vec()).map_err(|e| {
+                AppError::External(format!("Failed to generate certificate: {}", e))
+            })?;
+            let key = PrivatePkcs8KeyDer::from(certified.signing_key.serialize_der());
+            (vec![certified.cert.der().clone()], key.into())
+        }
+    };
+
+    // Pinned explicitly, reqwest may pull in a second crypto provider.
+    ServerConfig::builder_with_provider(Arc::new(aws_lc_rs::default_provider()))
+        .with_safe_default_protocol_versions()
+        .and_then(|builder| builder.with_no_client_auth().with_single_cert(chain, key))
+        .map(Arc::new)
+        .map_err(|e| AppError::External(format!("Invalid TLS configuration: {}", e)))
+}
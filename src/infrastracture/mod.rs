@@ -1,3 +1,11 @@
+pub mod composite;
+pub mod dns;
 pub mod file;
+pub mod geoip;
+pub mod judge_server;
+pub mod output;
+pub mod parser;
 pub mod proxy_test;
+pub mod stdin;
 pub mod string;
+pub mod url;
@@ -0,0 +1,164 @@
+use crate::core::application::{Error as AppError, ports::ResultWriter};
+use crate::core::domain::{
+    ProxyAnonymity, ProxyScheme, RejectedLine, SchemeResult, SocksProbe, SocksReply, TestFailure,
+    TestResult,
+};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tokio::fs;
+
+const ALL_FILE: &str = "all.txt";
+const FAILED_FILE: &str = "failed.txt";
+const SOCKS_PROBES_FILE: &str = "socks_probes.txt";
+
+/// Writes result lists into `dir` and rejected input lines into `rejects_file`;
+/// either may be absent.
+pub struct FileResultWriter {
+    dir: Option<PathBuf>,
+    rejects_file: Option<PathBuf>,
+}
+
+impl FileResultWriter {
+    pub fn new(dir: Option<PathBuf>, rejects_file: Option<PathBuf>) -> Self {
+        Self { dir, rejects_file }
+    }
+
+    fn file_name(category: impl ToString) -> String {
+        format!("{}.txt", category.to_string())
+    }
+}
+
+#[async_trait]
+impl ResultWriter for FileResultWriter {
+    async fn write(&self, results: &[TestResult]) -> Result<(), AppError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        // Every category file is (re)created so stale lists from a previous run never linger.
+        let mut files: BTreeMap<String, String> = BTreeMap::new();
+        files.insert(ALL_FILE.to_string(), String::new());
+        ProxyScheme::get_all_scheme()
+            .into_iter()
+            .for_each(|scheme| {
+                files.insert(Self::file_name(scheme), String::new());
+            });
+        ProxyAnonymity::get_all_anonymity()
+            .into_iter()
+            .for_each(|anonymity| {
+                files.insert(Self::file_name(anonymity), String::new());
+            });
+
+        let mut sorted: Vec<&TestResult> = results.iter().collect();
+        sorted.sort_by_key(|r| std::cmp::Reverse(r.score()));
+
+        for result in &sorted {
+            if let Some(content) = files.get_mut(ALL_FILE) {
+                content.push_str(&format!("{}\n", result.proxy_url()));
+            }
+        }
+
+        // A proxy lands in the scheme and anonymity files of every scheme it supports.
+        let mut schemes: Vec<(&TestResult, &SchemeResult)> = sorted
+            .iter()
+            .flat_map(|r| r.schemes().iter().map(move |s| (*r, s)))
+            .collect();
+        schemes.sort_by_key(|(_, s)| std::cmp::Reverse(s.score()));
+
+        for (result, scheme) in schemes {
+            let line = format!("{}\n", result.scheme_url(scheme));
+
+            for name in [
+                Self::file_name(scheme.scheme()),
+                Self::file_name(scheme.anonymity()),
+            ] {
+                if let Some(content) = files.get_mut(&name) {
+                    content.push_str(&line);
+                }
+            }
+        }
+
+        for (name, content) in files {
+            let path = dir.join(&name);
+            fs::write(&path, content).await.map_err(|e| {
+                AppError::External(format!("Failed to write '{}': {}", path.display(), e))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// One tab-separated `source line kind raw` row per rejected input line.
+    async fn write_rejects(&self, rejects: &[&RejectedLine]) -> Result<(), AppError> {
+        let Some(path) = &self.rejects_file else {
+            return Ok(());
+        };
+
+        let content: String = rejects
+            .iter()
+            .map(|r| {
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    r.source(),
+                    r.line(),
+                    r.error().kind(),
+                    r.raw()
+                )
+            })
+            .collect();
+
+        fs::write(path, content)
+            .await
+            .map_err(|e| AppError::External(format!("Failed to write '{}': {}", path.display(), e)))
+    }
+
+    /// One tab-separated `proxy kind reason` row per failed proxy.
+    async fn write_failures(&self, failures: &[TestFailure]) -> Result<(), AppError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        let content: String = failures
+            .iter()
+            .map(|f| format!("{}\t{}\t{}\n", f.proxy_url(), f.error().kind(), f.error()))
+            .collect();
+
+        let path = dir.join(FAILED_FILE);
+        fs::write(&path, content)
+            .await
+            .map_err(|e| AppError::External(format!("Failed to write '{}': {}", path.display(), e)))
+    }
+
+    /// One tab-separated `proxy auth ip_target domain_target` row per SOCKS handshake.
+    async fn write_socks_probes(&self, probes: &[(String, SocksProbe)]) -> Result<(), AppError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+
+        let target = |reply: Option<SocksReply>| reply.map_or("-".to_string(), |r| r.to_string());
+        let content: String = probes
+            .iter()
+            .map(|(url, probe)| {
+                let auth = probe
+                    .auth_methods()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    url,
+                    if auth.is_empty() { "-" } else { &auth },
+                    target(probe.ip_target()),
+                    target(probe.domain_target())
+                )
+            })
+            .collect();
+
+        let path = dir.join(SOCKS_PROBES_FILE);
+        fs::write(&path, content)
+            .await
+            .map_err(|e| AppError::External(format!("Failed to write '{}': {}", path.display(), e)))
+    }
+}
@@ -0,0 +1,3 @@
+mod file_result_writer;
+
+pub use file_result_writer::FileResultWriter;
@@ -0,0 +1,62 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+use tokio::sync::mpsc;
+
+use super::ProxyParser;
+use crate::core::application::dto::InputFormat;
+use crate::core::domain::{Proxy, RejectedLine};
+
+/// Parses `reader` line by line and forwards every outcome to `tx` in input order,
+/// so that later stages such as deduplication see the same sequence on every run.
+pub async fn forward_lines<R>(
+    reader: R,
+    source: String,
+    format: InputFormat,
+    tx: mpsc::Sender<Result<Proxy, RejectedLine>>,
+) where
+    R: AsyncBufRead + Unpin,
+{
+    let mut parser = ProxyParser::new(format);
+    let mut lines = reader.lines();
+    let mut line_number = 0;
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        line_number += 1;
+
+        let parsed = match parser.parse_line(&line) {
+            Some(Ok(proxy)) => Ok(proxy),
+            Some(Err(e)) => Err(RejectedLine::new(&source, line_number, &line, e)),
+            None => continue,
+        };
+
+        if tx.send(parsed).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn forwards_proxies_and_rejects_in_input_order() {
+        let input: &[u8] =
+            b"http://1.1.1.1:80\nnot a proxy\n\nsocks5://2.2.2.2:1080\n3.3.3.3:8080\n";
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let forward = forward_lines(input, "list.txt".to_string(), InputFormat::Auto, tx);
+        let collect = async {
+            let mut seen = Vec::new();
+            while let Some(parsed) = rx.recv().await {
+                seen.push(match parsed {
+                    Ok(proxy) => proxy.ip().to_string(),
+                    Err(rejected) => format!("line {}", rejected.line()),
+                });
+            }
+            seen
+        };
+        let ((), seen) = tokio::join!(forward, collect);
+
+        assert_eq!(seen, ["1.1.1.1", "line 2", "2.2.2.2", "3.3.3.3"]);
+    }
+}
@@ -0,0 +1,5 @@
+mod line_stream;
+mod proxy_parser;
+
+pub use line_stream::forward_lines;
+pub use proxy_parser::ProxyParser;
@@ -0,0 +1,53 @@
+use std::io;
+
+use tokio_rustls::rustls;
+
+use crate::core::domain::error::ProxyTestError;
+
+pub fn from_io(e: &io::Error) -> ProxyTestError {
+    if e.get_ref().is_some_and(|inner| inner.is::<rustls::Error>()) {
+        return ProxyTestError::TlsError;
+    }
+
+    match e.kind() {
+        io::ErrorKind::ConnectionRefused => ProxyTestError::ConnectionRefused,
+        io::ErrorKind::TimedOut => ProxyTestError::Timeout,
+        io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::BrokenPipe
+        | io::ErrorKind::UnexpectedEof => ProxyTestError::ConnectionReset,
+        _ => ProxyTestError::ConnectionFailed,
+    }
+}
+
+/// A non-2xx status the proxy itself answered with.
+pub fn from_proxy_status(status: u16) -> ProxyTestError {
+    match status {
+        407 => ProxyTestError::AuthRequired,
+        _ => ProxyTestError::ProxyRejected(format!("HTTP {}", status)),
+    }
+}
+
+pub fn from_socks(e: tokio_socks::Error) -> ProxyTestError {
+    use tokio_socks::Error as SocksError;
+
+    match e {
+        SocksError::Io(e) => from_io(&e),
+        SocksError::NoAcceptableAuthMethods
+        | SocksError::AuthorizationRequired
+        | SocksError::PasswordAuthFailure(_)
+        | SocksError::IdentdAuthFailure
+        | SocksError::InvalidUserIdAuthFailure => ProxyTestError::AuthRequired,
+        SocksError::NetworkUnreachable
+        | SocksError::HostUnreachable
+        | SocksError::ConnectionRefused
+        | SocksError::TtlExpired => ProxyTestError::JudgeUnreachable,
+        SocksError::GeneralSocksServerFailure
+        | SocksError::ConnectionNotAllowedByRuleset
+        | SocksError::CommandNotSupported
+        | SocksError::AddressTypeNotSupported => {
+            ProxyTestError::ProxyRejected(format!("SOCKS {}", e))
+        }
+        _ => ProxyTestError::NotAProxy,
+    }
+}
@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use url::Url;
+
+use super::failure;
+use crate::core::domain::{Proxy, ProxyScheme, error::ProxyTestError};
+
+/// SOCKS5 greeting offering "no auth" and "username/password".
+const SOCKS5_GREETING: &[u8] = &[0x05, 0x02, 0x00, 0x02];
+/// SOCKS4 CONNECT to 127.0.0.1:0, which any server refuses with a well-formed reply.
+const SOCKS4_REQUEST: &[u8] = &[0x04, 0x01, 0x00, 0x00, 0x7F, 0x00, 0x00, 0x01, 0x00];
+
+/// Minimal TLS 1.2 ClientHello; a TLS server answers with a handshake record
+/// or an alert, both of which identify it.
+const TLS_CLIENT_HELLO: &[u8] = &[
+    0x16, 0x03, 0x01, 0x00, 0x4B, // record: handshake, 75 bytes
+    0x01, 0x00, 0x00, 0x47, // ClientHello, 71 bytes
+    0x03, 0x03, // TLS 1.2
+    0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
+    0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x1C, 0x1D, 0x1E,
+    0x1F, // random
+    0x00, // no session id
+    0x00, 0x08, 0xC0, 0x2B, 0xC0, 0x2F, 0xC0, 0x2C, 0xC0, 0x30, // ECDHE-{ECDSA,RSA}-AES-GCM
+    0x01, 0x00, // null compression
+    0x00, 0x16, // extensions, 22 bytes
+    0x00, 0x0A, 0x00, 0x06, 0x00, 0x04, 0x00, 0x1D, 0x00, 0x17, // groups: x25519, P-256
+    0x00, 0x0D, 0x00, 0x08, 0x00, 0x06, 0x04, 0x03, 0x08, 0x04, 0x04, 0x01, // signatures
+];
+
+/// Identifies which protocols a scheme-less proxy speaks before the full test,
+/// so only those schemes are tried.
+pub struct ProtocolFingerprinter {
+    timeout: Duration,
+    http_connect: Vec<u8>,
+}
+
+impl ProtocolFingerprinter {
+    /// HTTP proxies are asked to CONNECT to `target`, the judge they will be
+    /// tested against, so the probe does not depend on any other host.
+    pub fn new(timeout: Duration, target: &Url) -> Self {
+        let authority = format!(
+            "{}:{}",
+            target.host_str().unwrap_or_default(),
+            target.port_or_known_default().unwrap_or(80)
+        );
+
+        Self {
+            timeout,
+            http_connect: format!(
+                "CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n\r\n",
+                authority = authority
+            )
+            .into_bytes(),
+        }
+    }
+
+    /// Returns the detected schemes in `ProxyScheme::get_all_scheme` order.
+    pub async fn detect(&self, proxy: &Proxy) -> Result<Vec<ProxyScheme>, ProxyTestError> {
+        // A dead proxy fails here once instead of once per scheme and attempt.
+        let first = self.connect(proxy).await?;
+
+        let (socks5, socks4, http, tls) = tokio::join!(
+            self.exchange(proxy, Some(first), SOCKS5_GREETING, 2),
+            self.exchange(proxy, None, SOCKS4_REQUEST, 8),
+            self.exchange(proxy, None, &self.http_connect, 5),
+            self.exchange(proxy, None, TLS_CLIENT_HELLO, 3),
+        );
+
+        let mut schemes = Vec::new();
+
+        if http.is_some_and(|reply| reply.starts_with(b"HTTP/")) {
+            schemes.push(ProxyScheme::Http);
+        }
+        if tls.is_some_and(|reply| matches!(reply[..], [0x15 | 0x16, 0x03, _])) {
+            schemes.push(ProxyScheme::Https);
+        }
+        if socks5.is_some_and(|reply| reply[0] == 0x05) {
+            schemes.extend([ProxyScheme::Socks5h, ProxyScheme::Socks5]);
+        }
+        if socks4.is_some_and(|reply| reply[0] == 0x00 && (0x5A..=0x5D).contains(&reply[1])) {
+            schemes.push(ProxyScheme::Socks4);
+        }
+
+        Ok(schemes)
+    }
+
+    async fn connect(&self, proxy: &Proxy) -> Result<TcpStream, ProxyTestError> {
+        let addr = format!("{}:{}", proxy.ip(), proxy.port());
+
+        match timeout(self.timeout, TcpStream::connect(addr)).await {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(Err(e)) => Err(failure::from_io(&e)),
+            Err(_) => Err(ProxyTestError::Timeout),
+        }
+    }
+
+    /// Sends `probe` on `stream` or a fresh connection and reads the first
+    /// `len` bytes of the answer, if any.
+    async fn exchange(
+        &self,
+        proxy: &Proxy,
+        stream: Option<TcpStream>,
+        probe: &[u8],
+        len: usize,
+    ) -> Option<Vec<u8>> {
+        let mut stream = match stream {
+            Some(stream) => stream,
+            None => self.connect(proxy).await.ok()?,
+        };
+        let mut reply = vec![0u8; len];
+
+        timeout(self.timeout, async {
+            stream.write_all(probe).await?;
+            stream.read_exact(&mut reply).await
+        })
+        .await
+        .ok()?
+        .ok()?;
+
+        Some(reply)
+    }
+}
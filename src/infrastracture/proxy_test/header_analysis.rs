@@ -1,29 +1,292 @@
-use crate::core::domain::ProxyAnonymity;
+use std::net::IpAddr;
 
-pub fn analyze_headers(body: &str) -> (bool, bool) {
-    let b = body.to_lowercase();
+use crate::core::domain::{LeakKind, LeakedHeader, ProxyAnonymity};
 
-    let transparent_headers = ["x-forwarded-for", "x-real-ip", "client-ip"];
+/// Headers whose value is the address of the client behind the proxy.
+const CLIENT_IP_HEADERS: &[&str] = &[
+    "x-forwarded-for",
+    "x-real-ip",
+    "client-ip",
+    "x-client-ip",
+    "true-client-ip",
+    "x-proxyuser-ip",
+    "x-originating-ip",
+    "cf-connecting-ip",
+];
 
-    let proxy_headers = ["via", "forwarded", "proxy-connection"];
+/// Headers that only tell a proxy handled the request.
+const PROXY_HEADERS: &[&str] = &[
+    "via",
+    "proxy-connection",
+    "x-forwarded-host",
+    "x-forwarded-proto",
+    "x-forwarded-port",
+    "x-forwarded-server",
+    "x-proxy-id",
+    "x-bluecoat-via",
+];
 
-    let has_transparent = transparent_headers.iter().any(|h| b.contains(h));
-    let has_proxy = proxy_headers.iter().any(|h| b.contains(h));
+/// Picks the revealing headers out of the ones the judge echoed back.
+/// `Forwarded` counts as a client-IP leak only when it has a `for=` parameter.
+pub fn analyze_headers(headers: &[(String, String)]) -> Vec<LeakedHeader> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let name = name.to_lowercase();
+            let kind = if CLIENT_IP_HEADERS.contains(&name.as_str()) {
+                LeakKind::ClientIp
+            } else if name == "forwarded" {
+                if forwarded_for(value).next().is_some() {
+                    LeakKind::ClientIp
+                } else {
+                    LeakKind::Proxy
+                }
+            } else if PROXY_HEADERS.contains(&name.as_str()) {
+                LeakKind::Proxy
+            } else {
+                return None;
+            };
+            Some(LeakedHeader::new(&name, value, kind))
+        })
+        .collect()
+}
+
+/// Addresses named in a client-IP header, ports, brackets and quotes stripped.
+pub fn leaked_ips(header: &LeakedHeader) -> Vec<IpAddr> {
+    if header.kind() != LeakKind::ClientIp {
+        return Vec::new();
+    }
+
+    let values: Vec<&str> = if header.name() == "forwarded" {
+        forwarded_for(header.value()).collect()
+    } else {
+        header.value().split(',').collect()
+    };
+
+    values
+        .into_iter()
+        .filter_map(|value| parse_ip(value.trim().trim_matches('"')))
+        .map(|ip| ip.to_canonical())
+        .collect()
+}
+
+/// `for=` values of an RFC 7239 `Forwarded` header.
+fn forwarded_for(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split([',', ';'])
+        .filter_map(|pair| pair.trim().split_once('='))
+        .filter(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+        .map(|(_, value)| value.trim())
+}
+
+/// Accepts `ip`, `ip:port`, `[ipv6]` and `[ipv6]:port`.
+fn parse_ip(value: &str) -> Option<IpAddr> {
+    if let Ok(ip) = value.parse() {
+        return Some(ip);
+    }
+    if let Some(rest) = value.strip_prefix('[') {
+        return rest.split_once(']')?.0.parse().ok();
+    }
+    value.rsplit_once(':')?.0.parse().ok()
+}
 
-    (has_transparent, has_proxy)
+/// Private, loopback and link-local addresses, which proxy chains routinely
+/// forward for their internal hops.
+fn is_internal(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+        IpAddr::V6(ip) => ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local(),
+    }
 }
 
+/// `real_ips` are every egress address we have; exiting through any of them,
+/// or having one of them forwarded in a header, means the proxy did not hide us.
+/// A forwarded public address that is neither ours nor the proxy's exit is made
+/// up; internal hops are not.
+/// Without an exit IP or any echoed header the judge gave us nothing to go on.
 pub fn classify_proxy(
-    real_ip: &str,
-    proxy_ip: &str,
-    has_transparent_hdr: bool,
-    has_proxy_hdr: bool,
+    real_ips: &[IpAddr],
+    proxy_ip: Option<&str>,
+    headers: &[(String, String)],
+    leaks: &[LeakedHeader],
 ) -> ProxyAnonymity {
-    if proxy_ip == real_ip || has_transparent_hdr {
+    let Some(proxy_ip) = proxy_ip.and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+        return ProxyAnonymity::Unknown;
+    };
+    let proxy_ip = proxy_ip.to_canonical();
+    let forwarded: Vec<IpAddr> = leaks.iter().flat_map(leaked_ips).collect();
+
+    if real_ips.contains(&proxy_ip) || forwarded.iter().any(|ip| real_ips.contains(ip)) {
         ProxyAnonymity::Transparent
-    } else if has_proxy_hdr {
+    } else if headers.is_empty() {
+        ProxyAnonymity::Unknown
+    } else if forwarded
+        .iter()
+        .any(|ip| *ip != proxy_ip && !is_internal(ip))
+    {
+        ProxyAnonymity::Distorting
+    } else if !leaks.is_empty() {
         ProxyAnonymity::Anonymous
     } else {
         ProxyAnonymity::Elite
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REAL_IP: &str = "203.0.113.7";
+    const EXIT_IP: &str = "198.51.100.20";
+
+    fn headers(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn classify(proxy_ip: Option<&str>, pairs: &[(&str, &str)]) -> ProxyAnonymity {
+        let headers = headers(pairs);
+        let leaks = analyze_headers(&headers);
+        classify_proxy(&[REAL_IP.parse().unwrap()], proxy_ip, &headers, &leaks)
+    }
+
+    #[test]
+    fn picks_revealing_headers_only() {
+        let leaks = analyze_headers(&headers(&[
+            ("Host", "judge"),
+            ("X-Real-IP", "203.0.113.7"),
+            ("Via", "1.1 squid"),
+            ("Accept", "*/*"),
+        ]));
+        assert_eq!(leaks.len(), 2);
+        assert_eq!(leaks[0].name(), "x-real-ip");
+        assert_eq!(leaks[0].kind(), LeakKind::ClientIp);
+        assert_eq!(leaks[1].name(), "via");
+        assert_eq!(leaks[1].kind(), LeakKind::Proxy);
+    }
+
+    #[test]
+    fn leaked_ips_strip_ports_brackets_and_quotes() {
+        let leaks = analyze_headers(&headers(&[
+            (
+                "X-Forwarded-For",
+                "203.0.113.7:4711, [2001:db8::1]:80, unknown",
+            ),
+            (
+                "Forwarded",
+                "for=\"[2001:db8::2]\";proto=http, for=192.0.2.1",
+            ),
+        ]));
+        assert_eq!(
+            leaked_ips(&leaks[0]),
+            vec![
+                "203.0.113.7".parse::<IpAddr>().unwrap(),
+                "2001:db8::1".parse().unwrap()
+            ]
+        );
+        assert_eq!(
+            leaked_ips(&leaks[1]),
+            vec![
+                "2001:db8::2".parse::<IpAddr>().unwrap(),
+                "192.0.2.1".parse().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn forwarded_without_for_is_a_proxy_leak() {
+        let leaks = analyze_headers(&headers(&[("Forwarded", "proto=https")]));
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].kind(), LeakKind::Proxy);
+        assert!(leaked_ips(&leaks[0]).is_empty());
+    }
+
+    #[test]
+    fn unknown_without_exit_ip_or_headers() {
+        assert_eq!(
+            classify(None, &[("Host", "judge")]),
+            ProxyAnonymity::Unknown
+        );
+        assert_eq!(
+            classify(Some("garbage"), &[("Host", "judge")]),
+            ProxyAnonymity::Unknown
+        );
+        assert_eq!(classify(Some(EXIT_IP), &[]), ProxyAnonymity::Unknown);
+    }
+
+    #[test]
+    fn transparent_when_our_address_shows() {
+        assert_eq!(classify(Some(REAL_IP), &[]), ProxyAnonymity::Transparent);
+        assert_eq!(
+            classify(
+                Some(EXIT_IP),
+                &[("X-Forwarded-For", "203.0.113.7, 10.0.0.1")]
+            ),
+            ProxyAnonymity::Transparent
+        );
+        assert_eq!(
+            classify(
+                Some(EXIT_IP),
+                &[("Forwarded", "for=\"203.0.113.7:4711\";proto=http")]
+            ),
+            ProxyAnonymity::Transparent
+        );
+    }
+
+    #[test]
+    fn distorting_when_a_foreign_address_is_forwarded() {
+        assert_eq!(
+            classify(Some(EXIT_IP), &[("X-Real-IP", "192.0.2.55")]),
+            ProxyAnonymity::Distorting
+        );
+    }
+
+    #[test]
+    fn anonymous_when_only_proxy_headers_leak() {
+        assert_eq!(
+            classify(Some(EXIT_IP), &[("Host", "judge"), ("Via", "1.1 squid")]),
+            ProxyAnonymity::Anonymous
+        );
+        // Forwarding the exit address itself reveals the proxy but not us.
+        assert_eq!(
+            classify(Some(EXIT_IP), &[("X-Forwarded-For", EXIT_IP)]),
+            ProxyAnonymity::Anonymous
+        );
+    }
+
+    #[test]
+    fn internal_hops_are_not_distorting() {
+        for hop in [
+            "10.0.0.5",
+            "192.168.1.20, 127.0.0.1",
+            "169.254.0.9",
+            "fd00::5",
+            "fe80::1",
+        ] {
+            assert_eq!(
+                classify(Some(EXIT_IP), &[("X-Forwarded-For", hop)]),
+                ProxyAnonymity::Anonymous,
+                "{}",
+                hop
+            );
+        }
+        // A public address next to an internal hop still gives the proxy away.
+        assert_eq!(
+            classify(
+                Some(EXIT_IP),
+                &[("X-Forwarded-For", "10.0.0.5, 192.0.2.55")]
+            ),
+            ProxyAnonymity::Distorting
+        );
+    }
+
+    #[test]
+    fn elite_when_headers_are_clean() {
+        assert_eq!(
+            classify(Some(EXIT_IP), &[("Host", "judge"), ("Accept", "*/*")]),
+            ProxyAnonymity::Elite
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use serde_json::Value;
+use url::Url;
+
+use crate::core::application::dto::{JudgeFormat, JudgeUrl};
+use crate::core::domain::ProxyScheme;
+
+/// IPv4-only, dual-stack and an independent provider, so both address families
+/// are found and one outage does not stop the run.
+const DEFAULT_REAL_IP_URLS: &[&str] = &[
+    "https://api.ipify.org",
+    "https://api64.ipify.org",
+    "https://ipinfo.io/ip",
+];
+
+/// Where the tester sends its probes, per proxy scheme.
+#[derive(Debug, Clone)]
+pub struct JudgeConfig {
+    ip_urls: HashMap<ProxyScheme, Url>,
+    header_urls: HashMap<ProxyScheme, Url>,
+    real_ip_urls: Vec<Url>,
+    format: JudgeFormat,
+    accept_invalid_certs: bool,
+}
+
+impl Default for JudgeConfig {
+    /// Public echo services; HTTP proxies are probed over plain HTTP, the rest over HTTPS.
+    fn default() -> Self {
+        let mut ip_urls = HashMap::new();
+        let mut header_urls = HashMap::new();
+
+        for scheme in ProxyScheme::get_all_scheme() {
+            let (ip_url, header_url) = match scheme {
+                ProxyScheme::Http => ("http://httpbin.org/ip", "http://httpbin.org/headers"),
+                _ => ("https://ipinfo.io/ip", "https://httpbin.org/headers"),
+            };
+            ip_urls.insert(scheme.clone(), Url::parse(ip_url).unwrap());
+            header_urls.insert(scheme, Url::parse(header_url).unwrap());
+        }
+
+        Self {
+            ip_urls,
+            header_urls,
+            real_ip_urls: DEFAULT_REAL_IP_URLS
+                .iter()
+                .map(|url| Url::parse(url).unwrap())
+                .collect(),
+            format: JudgeFormat::Auto,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+impl JudgeConfig {
+    /// `judges` echo both the client IP and the request headers, `ip_judges` only
+    /// override the IP endpoint. Unscoped URLs apply to every scheme and
+    /// scheme-scoped ones take precedence regardless of their order.
+    /// `accept_invalid_certs` allows self-signed judges such as `judge` itself.
+    /// Empty `real_ip_urls` keep the default lookup endpoints.
+    pub fn new(
+        judges: &[JudgeUrl],
+        ip_judges: &[JudgeUrl],
+        real_ip_urls: Vec<Url>,
+        format: JudgeFormat,
+        accept_invalid_certs: bool,
+    ) -> Self {
+        let mut config = Self {
+            format,
+            accept_invalid_certs,
+            ..Self::default()
+        };
+
+        if !real_ip_urls.is_empty() {
+            config.real_ip_urls = real_ip_urls;
+        }
+
+        let scoped_last = |urls: &[JudgeUrl]| -> Vec<JudgeUrl> {
+            let (mut ordered, scoped): (Vec<JudgeUrl>, Vec<JudgeUrl>) =
+                urls.iter().cloned().partition(|j| j.scheme.is_none());
+            ordered.extend(scoped);
+            ordered
+        };
+
+        for judge in scoped_last(judges) {
+            config.set(&judge, true);
+        }
+        for judge in scoped_last(ip_judges) {
+            config.set(&judge, false);
+        }
+
+        config
+    }
+
+    fn set(&mut self, judge: &JudgeUrl, with_headers: bool) {
+        let schemes = match &judge.scheme {
+            Some(scheme) => vec![scheme.clone()],
+            None => ProxyScheme::get_all_scheme(),
+        };
+
+        for scheme in schemes {
+            self.ip_urls.insert(scheme.clone(), judge.url.clone());
+            if with_headers {
+                self.header_urls.insert(scheme, judge.url.clone());
+            }
+        }
+    }
+
+    pub fn ip_url(&self, scheme: &ProxyScheme) -> &Url {
+        &self.ip_urls[scheme]
+    }
+
+    pub fn header_url(&self, scheme: &ProxyScheme) -> &Url {
+        &self.header_urls[scheme]
+    }
+
+    pub fn real_ip_urls(&self) -> &[Url] {
+        &self.real_ip_urls
+    }
+
+    pub fn format(&self) -> JudgeFormat {
+        self.format
+    }
+
+    pub fn accept_invalid_certs(&self) -> bool {
+        self.accept_invalid_certs
+    }
+}
+
+/// Extracts the client address a judge saw from its response body.
+pub fn parse_ip(format: JudgeFormat, body: &str) -> Option<String> {
+    let ip = match resolve_format(format, body) {
+        JudgeFormat::Httpbin => {
+            let value: Value = serde_json::from_str(body).ok()?;
+            // httpbin lists every hop in `origin`, the first one is the client.
+            value
+                .get("origin")
+                .or_else(|| value.get("ip"))
+                .and_then(Value::as_str)
+                .and_then(|origin| origin.split(',').next())
+                .map(str::to_string)
+        }
+        JudgeFormat::Azenv => azenv_pairs(body)
+            .find(|(key, _)| key == "REMOTE_ADDR")
+            .map(|(_, value)| value),
+        JudgeFormat::Plain | JudgeFormat::Auto => Some(body.to_string()),
+    }?;
+
+    let ip = ip.trim();
+    ip.parse::<IpAddr>().ok().map(|_| ip.to_string())
+}
+
+/// Extracts the request headers a judge echoed back, with lowercase names.
+pub fn parse_headers(format: JudgeFormat, body: &str) -> Vec<(String, String)> {
+    match resolve_format(format, body) {
+        JudgeFormat::Httpbin => serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|value| value.get("headers").and_then(Value::as_object).cloned())
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = value
+                            .as_str()
+                            .map_or_else(|| value.to_string(), String::from);
+                        (name.to_lowercase(), value)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        JudgeFormat::Azenv => azenv_pairs(body)
+            .filter_map(|(key, value)| {
+                key.strip_prefix("HTTP_")
+                    .map(|name| (name.to_lowercase().replace('_', "-"), value))
+            })
+            .collect(),
+        JudgeFormat::Plain | JudgeFormat::Auto => body
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .filter(|(name, _)| !name.is_empty() && !name.contains(' '))
+            .collect(),
+    }
+}
+
+fn resolve_format(format: JudgeFormat, body: &str) -> JudgeFormat {
+    if format != JudgeFormat::Auto {
+        return format;
+    }
+
+    let trimmed = body.trim_start();
+    if trimmed.starts_with('{') {
+        JudgeFormat::Httpbin
+    } else if body.contains("REMOTE_ADDR") || body.contains("HTTP_HOST") {
+        JudgeFormat::Azenv
+    } else {
+        JudgeFormat::Plain
+    }
+}
+
+/// `KEY = value` lines; azenv scripts often wrap them in HTML, which is skipped.
+fn azenv_pairs(body: &str) -> impl Iterator<Item = (String, String)> + '_ {
+    body.lines().filter_map(|line| {
+        let (key, value) = line.split_once(" = ")?;
+        let key = key.trim().trim_start_matches("<pre>").trim();
+        key.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            .then(|| (key.to_string(), value.trim().to_string()))
+    })
+}
@@ -1,5 +1,20 @@
+mod failure;
+mod fingerprint;
 mod header_analysis;
+mod judge;
+mod real_ip;
 mod reqwest_test_service;
+mod retry_policy;
 mod scoring;
+mod test_plan;
+mod throughput;
+mod timed_transport;
+mod tokio_socks_prober;
 
+pub use judge::JudgeConfig;
+pub use real_ip::RealIpResolver;
 pub use reqwest_test_service::ReqwestProxyTestService;
+pub use retry_policy::RetryPolicy;
+pub use test_plan::TestPlan;
+pub use throughput::ThroughputProbe;
+pub use tokio_socks_prober::TokioSocksProber;
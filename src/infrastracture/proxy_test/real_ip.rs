@@ -0,0 +1,138 @@
+use std::net::IpAddr;
+use std::time::Duration;
+
+use futures::future::join_all;
+use reqwest::Client;
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::core::application::dto::JudgeFormat;
+use crate::core::domain::error::ProxyTestError;
+
+use super::judge::{JudgeConfig, parse_ip};
+
+/// Our own public addresses, which a transparent proxy leaks to the judge.
+/// Owned by one tester so testers behind different networks do not share them.
+pub struct RealIpResolver {
+    known: Vec<IpAddr>,
+    urls: Vec<Url>,
+    timeout: Duration,
+    accept_invalid_certs: bool,
+    resolved: OnceCell<Vec<IpAddr>>,
+}
+
+impl RealIpResolver {
+    /// `known` addresses are used as given; otherwise every real-IP URL of
+    /// `judge` is queried, so dual-stack hosts and NAT pools report all
+    /// their egress addresses and one dead endpoint does not fail the run.
+    pub fn new(known: Vec<IpAddr>, judge: &JudgeConfig, timeout: u8) -> Self {
+        Self {
+            known: known.into_iter().map(|ip| ip.to_canonical()).collect(),
+            urls: judge.real_ip_urls().to_vec(),
+            timeout: Duration::from_secs(timeout as u64),
+            accept_invalid_certs: judge.accept_invalid_certs(),
+            resolved: OnceCell::new(),
+        }
+    }
+
+    /// Looked up once; a failed lookup is retried by the next caller.
+    pub async fn get(&self) -> Result<&[IpAddr], ProxyTestError> {
+        if !self.known.is_empty() {
+            return Ok(&self.known);
+        }
+
+        self.resolved
+            .get_or_try_init(|| self.lookup())
+            .await
+            .map(Vec::as_slice)
+    }
+
+    async fn lookup(&self) -> Result<Vec<IpAddr>, ProxyTestError> {
+        // Without our own address no proxy can be judged, whatever the cause.
+        let client = Client::builder()
+            .timeout(self.timeout)
+            .tls_danger_accept_invalid_certs(self.accept_invalid_certs)
+            .build()
+            .map_err(|_| ProxyTestError::JudgeUnreachable)?;
+
+        let found = join_all(self.urls.iter().map(|url| self.query(&client, url))).await;
+
+        let mut ips: Vec<IpAddr> = Vec::new();
+        for ip in found.into_iter().flatten() {
+            if !ips.contains(&ip) {
+                ips.push(ip);
+            }
+        }
+
+        if ips.is_empty() {
+            return Err(ProxyTestError::JudgeUnreachable);
+        }
+
+        Ok(ips)
+    }
+
+    async fn query(&self, client: &Client, url: &Url) -> Option<IpAddr> {
+        let response = client.get(url.clone()).send().await.ok()?;
+        let body = response.error_for_status().ok()?.text().await.ok()?;
+
+        parse_real_ip(&body)
+    }
+}
+
+/// Lookup services answer in their own format, whatever `--judge-format` says
+/// about the judges, so the body is always sniffed.
+fn parse_real_ip(body: &str) -> Option<IpAddr> {
+    parse_ip(JudgeFormat::Auto, body)?
+        .parse::<IpAddr>()
+        .ok()
+        .map(|ip| ip.to_canonical())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_plain_and_json_lookup_bodies() {
+        assert_eq!(
+            parse_real_ip("203.0.113.7\n"),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(
+            parse_real_ip(r#"{"origin": "203.0.113.7"}"#),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(
+            parse_real_ip("::ffff:203.0.113.7"),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(parse_real_ip("<html>blocked</html>"), None);
+    }
+
+    #[tokio::test]
+    async fn lookup_ignores_the_judge_format() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            let _ = stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 12\r\nConnection: close\r\n\r\n203.0.113.7\n",
+                )
+                .await;
+        });
+
+        let url = Url::parse(&format!("http://{}/ip", addr)).unwrap();
+        let judge = JudgeConfig::new(&[], &[], vec![url], JudgeFormat::Azenv, false);
+        let resolver = RealIpResolver::new(Vec::new(), &judge, 5);
+
+        assert_eq!(
+            resolver.get().await.unwrap(),
+            &["203.0.113.7".parse::<IpAddr>().unwrap()]
+        );
+    }
+}
@@ -1,183 +1,270 @@
-use std::{sync::Arc, time::Duration};
+use std::{net::IpAddr, time::Duration};
 
 use async_trait::async_trait;
-use dashmap::DashMap;
-use once_cell::sync::OnceCell;
-use reqwest::Client;
-use tokio::time::Instant;
+use futures::{StreamExt, stream};
+use tokio::time::{Instant, timeout_at};
 
 use crate::core::{
-    application::ports::ProxyTestPort,
+    application::{Error as AppError, ports::ProxyTestPort},
     domain::{
-        Proxy, ProxyAnonymity, ProxyCacheKey, ProxyScheme, TestResult, error::ProxyTestError,
+        LatencyStats, PhaseTimings, Proxy, ProxyScheme, SchemeResult, TestResult,
+        error::ProxyTestError,
     },
 };
 
 use super::{
+    fingerprint::ProtocolFingerprinter,
     header_analysis::{analyze_headers, classify_proxy},
+    judge::{JudgeConfig, parse_headers, parse_ip},
+    real_ip::RealIpResolver,
+    retry_policy::RetryPolicy,
     scoring::calculate_score,
+    test_plan::TestPlan,
+    throughput::ThroughputProbe,
+    timed_transport::TimedTransport,
 };
 
-static REAL_IP: OnceCell<String> = OnceCell::new();
-type ClientCache = Arc<DashMap<ProxyCacheKey, Client>>;
-
 pub struct ReqwestProxyTestService {
-    timeout: Duration,
-    cache: ClientCache,
+    judge: JudgeConfig,
+    real_ips: RealIpResolver,
+    fingerprinter: ProtocolFingerprinter,
+    transport: TimedTransport,
+    retry: RetryPolicy,
+    plan: TestPlan,
+    throughput: Option<ThroughputProbe>,
 }
 
 impl ReqwestProxyTestService {
-    pub fn new(timeout: u8) -> Self {
-        Self {
-            timeout: Duration::from_secs(timeout as u64),
-            cache: Arc::new(DashMap::new()),
-        }
+    pub fn new(
+        timeout: u8,
+        judge: JudgeConfig,
+        real_ips: RealIpResolver,
+        retry: RetryPolicy,
+        plan: TestPlan,
+        throughput: Option<ThroughputProbe>,
+    ) -> Result<Self, AppError> {
+        let timeout = Duration::from_secs(timeout as u64);
+
+        Ok(Self {
+            fingerprinter: ProtocolFingerprinter::new(timeout, judge.ip_url(&ProxyScheme::Http)),
+            transport: TimedTransport::new(timeout, judge.accept_invalid_certs())?,
+            judge,
+            real_ips,
+            retry,
+            plan,
+            throughput,
+        })
     }
 
-    fn get_or_create_client(
+    /// Returns the exit IP if the judge response contains one, the echoed
+    /// headers and the timings of the header request. A judge that echoes both is queried once, otherwise the IP and
+    /// header endpoints are hit concurrently. Both go through `transport`, so
+    /// whichever fails first is reported the same way.
+    async fn query_judge(
         &self,
-        key: &ProxyCacheKey,
-        scheme: ProxyScheme,
-    ) -> Result<Client, ProxyTestError> {
-        if let Some(client) = self.cache.get(key) {
-            return Ok(client.clone());
-        }
-
-        let proxy = reqwest::Proxy::all(format!("{}://{}", scheme, key.url()))
-            .map_err(|_| ProxyTestError::ConnectionFailed)?;
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+    ) -> Result<(Option<String>, Vec<(String, String)>, PhaseTimings), ProxyTestError> {
+        let ip_url = self.judge.ip_url(scheme);
+        let header_url = self.judge.header_url(scheme);
+        let format = self.judge.format();
 
-        let client = Client::builder()
-            .proxy(proxy)
-            .timeout(self.timeout)
-            .pool_max_idle_per_host(2)
-            .tcp_keepalive(Duration::from_secs(10))
-            .pool_idle_timeout(Duration::from_secs(15))
-            .build()
-            .map_err(|_| ProxyTestError::ConnectionFailed)?;
+        let ((header_body, timings), ip_body) = if ip_url == header_url {
+            let (body, timings) = self.transport.get(proxy, scheme, header_url).await?;
+            ((body.clone(), timings), body)
+        } else {
+            tokio::try_join!(self.transport.get(proxy, scheme, header_url), async {
+                let (body, _) = self.transport.get(proxy, scheme, ip_url).await?;
+                Ok(body)
+            })?
+        };
 
-        self.cache.insert(key.clone(), client.clone());
+        let proxy_ip = parse_ip(format, &ip_body);
 
-        Ok(client)
+        Ok((proxy_ip, parse_headers(format, &header_body), timings))
     }
 
-    async fn get_real_ip(&self) -> Result<String, ProxyTestError> {
-        if let Some(ip) = REAL_IP.get() {
-            return Ok(ip.clone());
+    async fn test_scheme(
+        &self,
+        proxy: &Proxy,
+        scheme: ProxyScheme,
+    ) -> Result<SchemeResult, ProxyTestError> {
+        let mut failure = None;
+
+        for attempt in 0..self.retry.max_attempts() {
+            match self.try_scheme(proxy, scheme.clone(), attempt).await {
+                Ok(result) => return Ok(result),
+                Err(e) => {
+                    let retry = self.retry.should_retry(attempt, &e);
+                    failure = furthest(failure, e);
+                    if !retry {
+                        break;
+                    }
+                    tokio::time::sleep(self.retry.delay(attempt)).await;
+                }
+            }
         }
 
-        let ip = Client::new()
-            .get("https://api.ipify.org")
-            .send()
-            .await
-            .map_err(|_| ProxyTestError::TestFailed)?
-            .text()
-            .await
-            .map_err(|_| ProxyTestError::TestFailed)?
-            .trim()
-            .to_string();
-
-        let _ = REAL_IP.set(ip.clone());
-        Ok(ip)
+        Err(failure.unwrap_or(ProxyTestError::ConnectionFailed))
     }
 
-    async fn get_proxy_ip(
+    /// Takes the latency samples and throughput of a working scheme, when
+    /// asked for and before `deadline`, and rescores it on them.
+    async fn measure(
         &self,
-        client: &Client,
-        scheme: ProxyScheme,
-    ) -> Result<String, ProxyTestError> {
-        let ip = client
-            .get(scheme.get_test_url())
-            .send()
-            .await
-            .map_err(|_| ProxyTestError::TestFailed)?
-            .text()
-            .await
-            .map_err(|_| ProxyTestError::TestFailed)?;
-
-        Ok(ip.trim().to_string())
+        proxy: &Proxy,
+        result: SchemeResult,
+        deadline: Instant,
+    ) -> SchemeResult {
+        let stats = self.sample(proxy, &result, deadline).await;
+        let throughput = match &self.throughput {
+            Some(probe) => {
+                probe
+                    .measure(&self.transport, proxy, result.scheme(), deadline)
+                    .await
+            }
+            None => None,
+        };
+
+        if stats.is_none() && throughput.is_none() {
+            return result;
+        }
+
+        let scored_throughput =
+            throughput.filter(|_| self.throughput.as_ref().is_some_and(|p| p.score()));
+        let score = calculate_score(
+            result.timings(),
+            stats.as_ref(),
+            scored_throughput.as_ref(),
+            result.retries(),
+            self.retry.max_attempts(),
+            result.anonymity(),
+        );
+
+        result.with_measurements(stats, throughput, score)
     }
 
-    async fn get_headers(
+    /// Probes a working scheme `samples` times in a row, its first successful
+    /// attempt included; `None` with a single sample. Samples still due at
+    /// `deadline` are dropped rather than counted as failures.
+    async fn sample(
         &self,
-        client: &Client,
-        scheme: ProxyScheme,
-    ) -> Result<String, ProxyTestError> {
-        client
-            .get(scheme.get_header_test_url())
-            .send()
-            .await
-            .map_err(|_| ProxyTestError::TestFailed)?
-            .text()
-            .await
-            .map_err(|_| ProxyTestError::TestFailed)
+        proxy: &Proxy,
+        result: &SchemeResult,
+        deadline: Instant,
+    ) -> Option<LatencyStats> {
+        let samples = self.plan.samples();
+        if samples <= 1 {
+            return None;
+        }
+
+        let url = self.judge.header_url(result.scheme());
+        let mut latencies = vec![result.latency_ms()];
+        let mut attempted = 1;
+
+        while attempted < samples {
+            match timeout_at(deadline, self.transport.get(proxy, result.scheme(), url)).await {
+                Ok(Ok((_, timings))) => latencies.push(timings.total_ms()),
+                Ok(Err(_)) => {}
+                Err(_) => break,
+            }
+            attempted += 1;
+        }
+
+        LatencyStats::new(&latencies, attempted)
     }
 
+    /// `retries` is the number of attempts that failed before this one.
     async fn try_scheme(
         &self,
         proxy: &Proxy,
         scheme: ProxyScheme,
-    ) -> Result<ProxyAnonymity, ProxyTestError> {
-        let key = ProxyCacheKey::new(proxy.ip().clone(), proxy.port().clone(), scheme.clone());
+        retries: u8,
+    ) -> Result<SchemeResult, ProxyTestError> {
+        let real_ips = self.real_ips.get().await?;
 
-        let client = self.get_or_create_client(&key, scheme.clone())?;
+        let (proxy_ip, headers, timings) = self.query_judge(proxy, &scheme).await?;
 
-        let real_ip = self.get_real_ip().await?;
+        let leaks = analyze_headers(&headers);
 
-        let (proxy_ip, headers_body) = tokio::try_join!(
-            self.get_proxy_ip(&client, scheme.clone()),
-            self.get_headers(&client, scheme.clone())
-        )
-        .map_err(|_| ProxyTestError::TestFailed)?;
+        let anonymity = classify_proxy(real_ips, proxy_ip.as_deref(), &headers, &leaks);
+        let exit_ip = proxy_ip
+            .and_then(|ip| ip.parse::<IpAddr>().ok())
+            .map(|ip| ip.to_canonical());
+        let score = calculate_score(
+            &timings,
+            None,
+            None,
+            retries,
+            self.retry.max_attempts(),
+            &anonymity,
+        );
 
-        let (transparent_hdr, proxy_hdr) = analyze_headers(&headers_body);
-
-        Ok(classify_proxy(
-            &real_ip,
-            &proxy_ip,
-            transparent_hdr,
-            proxy_hdr,
+        Ok(SchemeResult::new(
+            scheme, timings, retries, anonymity, exit_ip, leaks, score,
         ))
     }
 }
 
 #[async_trait]
 impl ProxyTestPort for ReqwestProxyTestService {
+    /// Races the candidate schemes, at most `scheme_concurrency` at a time, and
+    /// keeps the best-scoring result reached before the per-proxy deadline, or
+    /// every working scheme with `all_schemes`. Kept schemes are then sampled
+    /// for latency statistics and throughput when asked for, within what is
+    /// left of the same deadline.
     async fn test(&self, proxy: Proxy) -> Result<TestResult, ProxyTestError> {
-        let schemes = proxy
-            .scheme()
-            .clone()
-            .map(|s| vec![s])
-            .unwrap_or_else(ProxyScheme::get_all_scheme);
-
-        for scheme in schemes {
-            let mut retries: u8 = 0;
-
-            for attempt in 0..=2 {
-                let start = Instant::now();
-
-                match self.try_scheme(&proxy, scheme.clone()).await {
-                    Ok(anonymity) => {
-                        let latency = start.elapsed().as_millis();
-                        let score = calculate_score(latency, retries, &anonymity);
-
-                        return Ok(TestResult::new(
-                            proxy.ip().clone(),
-                            proxy.port().clone(),
-                            scheme,
-                            latency,
-                            retries,
-                            anonymity,
-                            score,
-                        ));
-                    }
-                    Err(_) => {
-                        retries += 1;
-                        tokio::time::sleep(Duration::from_millis(100 * (attempt + 1))).await;
-                    }
-                }
+        let deadline = Instant::now() + self.plan.deadline();
+
+        let schemes = match proxy.scheme() {
+            Some(scheme) => vec![scheme.clone()],
+            None => timeout_at(deadline, self.fingerprinter.detect(&proxy))
+                .await
+                .map_err(|_| ProxyTestError::Timeout)??,
+        };
+        if schemes.is_empty() {
+            return Err(ProxyTestError::NotAProxy);
+        }
+
+        let mut attempts = stream::iter(schemes)
+            .map(|scheme| self.test_scheme(&proxy, scheme))
+            .buffer_unordered(self.plan.scheme_concurrency());
+        let mut working = Vec::new();
+        let mut failure = None;
+
+        loop {
+            match timeout_at(deadline, attempts.next()).await {
+                Ok(Some(Ok(result))) => working.push(result),
+                Ok(Some(Err(e))) => failure = furthest(failure, e),
+                Ok(None) | Err(_) => break,
             }
         }
 
-        Err(ProxyTestError::TestFailed)
+        drop(attempts);
+        let mut result = TestResult::new(proxy, working)
+            // Nothing finished before the deadline.
+            .ok_or(failure.unwrap_or(ProxyTestError::Timeout))?;
+        if !self.plan.all_schemes() {
+            result.keep_best();
+        }
+
+        if self.plan.samples() > 1 || self.throughput.is_some() {
+            let (proxy, schemes) = result.into_parts();
+            let mut measured = Vec::with_capacity(schemes.len());
+            for scheme in schemes {
+                measured.push(self.measure(&proxy, scheme, deadline).await);
+            }
+            result = TestResult::new(proxy, measured).expect("measuring keeps every scheme");
+        }
+
+        Ok(result)
+    }
+}
+
+/// Keeps the failure that got further, the earlier one on a tie.
+fn furthest(current: Option<ProxyTestError>, next: ProxyTestError) -> Option<ProxyTestError> {
+    match current {
+        Some(current) if current.stage() >= next.stage() => Some(current),
+        _ => Some(next),
     }
 }
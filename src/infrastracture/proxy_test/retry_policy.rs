@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::core::application::dto::BackoffStrategy;
+use crate::core::domain::error::{FailureKind, ProxyTestError};
+
+/// How often a scheme is attempted and how long to wait in between.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u8,
+    base_delay: Duration,
+    backoff: BackoffStrategy,
+    jitter: bool,
+    retry_on: Vec<FailureKind>,
+}
+
+impl RetryPolicy {
+    /// `retry_on` lists the failure kinds worth another attempt; the
+    /// others, such as a refused connection or missing credentials, will not
+    /// change on a retry.
+    pub fn new(
+        max_attempts: u8,
+        base_delay: Duration,
+        backoff: BackoffStrategy,
+        jitter: bool,
+        retry_on: Vec<FailureKind>,
+    ) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            base_delay,
+            backoff,
+            jitter,
+            retry_on,
+        }
+    }
+
+    pub fn max_attempts(&self) -> u8 {
+        self.max_attempts
+    }
+
+    /// Whether another attempt may follow `attempt` (0-based) failing with `error`.
+    pub fn should_retry(&self, attempt: u8, error: &ProxyTestError) -> bool {
+        attempt + 1 < self.max_attempts && self.retry_on.contains(&error.kind())
+    }
+
+    /// Pause after `attempt` (0-based) failed; jitter spreads it over ±50%.
+    pub fn delay(&self, attempt: u8) -> Duration {
+        let failures = attempt as u32 + 1;
+        let delay = match self.backoff {
+            BackoffStrategy::Constant => self.base_delay,
+            BackoffStrategy::Linear => self.base_delay.saturating_mul(failures),
+            BackoffStrategy::Exponential => self
+                .base_delay
+                .saturating_mul(2u32.saturating_pow(failures - 1)),
+        };
+
+        if self.jitter {
+            delay.mul_f64(rand::rng().random_range(0.5..1.5))
+        } else {
+            delay
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::domain::ProxyAnonymity;
+use crate::core::domain::{LatencyStats, PhaseTimings, ProxyAnonymity, Throughput};
 
 fn latency_score(latency_ms: u128) -> f64 {
     match latency_ms {
@@ -12,21 +12,50 @@ fn latency_score(latency_ms: u128) -> f64 {
     }
 }
 
-fn reliability_score(retries: u8) -> f64 {
-    match retries {
-        0 => 100.0,
-        1 => 80.0,
-        2 => 55.0,
-        3 => 30.0,
-        _ => 10.0,
+/// Scaled by the share of the retry budget used, so a proxy that needed its
+/// last attempt scores the same whatever `--max-attempts` is.
+fn reliability_score(retries: u8, max_attempts: u8) -> f64 {
+    if max_attempts <= 1 {
+        return 100.0;
+    }
+
+    let spent = retries as f64 / (max_attempts - 1) as f64;
+    100.0 - 60.0 * spent.min(1.0)
+}
+
+/// Share of the latency score kept for a given jitter between samples.
+fn jitter_factor(jitter_ms: u128) -> f64 {
+    match jitter_ms {
+        0..=25 => 1.0,
+        26..=75 => 0.9,
+        76..=150 => 0.8,
+        151..=300 => 0.65,
+        _ => 0.5,
+    }
+}
+
+fn throughput_score(bytes_per_sec: u64) -> f64 {
+    const MB: u64 = 1024 * 1024;
+
+    match bytes_per_sec {
+        b if b >= 10 * MB => 100.0,
+        b if b >= 5 * MB => 90.0,
+        b if b >= 2 * MB => 75.0,
+        b if b >= MB => 55.0,
+        b if b >= MB / 2 => 35.0,
+        b if b >= MB / 10 => 20.0,
+        _ => 5.0,
     }
 }
 
 fn anonymity_score(a: &ProxyAnonymity, latency_ms: u128) -> f64 {
     let base = match a {
         ProxyAnonymity::Elite => 100.0,
+        ProxyAnonymity::Distorting => 85.0,
         ProxyAnonymity::Anonymous => 70.0,
         ProxyAnonymity::Transparent => 30.0,
+        // Works, but nothing is known about what it reveals.
+        ProxyAnonymity::Unknown => 15.0,
     };
 
     let latency_factor = match latency_ms {
@@ -39,12 +68,31 @@ fn anonymity_score(a: &ProxyAnonymity, latency_ms: u128) -> f64 {
     base * latency_factor
 }
 
-pub fn calculate_score(latency_ms: u128, retries: u8, anonymity: &ProxyAnonymity) -> u8 {
-    let latency = latency_score(latency_ms);
-    let reliability = reliability_score(retries);
-    let anonymity = anonymity_score(anonymity, latency_ms);
+/// Time to first byte is weighed on its own because it isolates how fast the
+/// proxy relays, independent of our distance to it. With `stats` the median
+/// replaces the single total, jitter discounts the latency part and failed
+/// samples the reliability part. A `throughput` weighs in with 15%.
+pub fn calculate_score(
+    timings: &PhaseTimings,
+    stats: Option<&LatencyStats>,
+    throughput: Option<&Throughput>,
+    retries: u8,
+    max_attempts: u8,
+    anonymity: &ProxyAnonymity,
+) -> u8 {
+    let total_ms = stats.map_or(timings.total_ms(), LatencyStats::median_ms);
+    let jitter = stats.map_or(1.0, |s| jitter_factor(s.jitter_ms()));
+    let success = stats.map_or(1.0, LatencyStats::success_ratio);
 
-    let final_score = latency * 0.45 + reliability * 0.30 + anonymity * 0.25;
+    let latency =
+        (latency_score(total_ms) * 0.30 + latency_score(timings.ttfb_ms()) * 0.15) * jitter;
+    let reliability = reliability_score(retries, max_attempts) * success;
+    let anonymity = anonymity_score(anonymity, total_ms);
+
+    let mut final_score = latency + reliability * 0.30 + anonymity * 0.25;
+    if let Some(throughput) = throughput {
+        final_score = final_score * 0.85 + throughput_score(throughput.download_bps()) * 0.15;
+    }
 
     final_score.round().clamp(1.0, 100.0) as u8
 }
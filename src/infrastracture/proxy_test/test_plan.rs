@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+/// How the test of a single proxy is scheduled.
+#[derive(Debug, Clone, Copy)]
+pub struct TestPlan {
+    deadline: Duration,
+    scheme_concurrency: usize,
+    all_schemes: bool,
+    samples: u8,
+}
+
+impl TestPlan {
+    /// `deadline` bounds fingerprinting and every scheme and retry of one
+    /// proxy. The `samples` latency probes of each reported scheme follow
+    /// the race, one after the other, so they do not compete with each other.
+    pub fn new(deadline: u16, scheme_concurrency: usize, all_schemes: bool, samples: u8) -> Self {
+        Self {
+            deadline: Duration::from_secs(deadline as u64),
+            scheme_concurrency: scheme_concurrency.max(1),
+            all_schemes,
+            samples: samples.max(1),
+        }
+    }
+
+    pub fn deadline(&self) -> Duration {
+        self.deadline
+    }
+
+    pub fn scheme_concurrency(&self) -> usize {
+        self.scheme_concurrency
+    }
+
+    pub fn all_schemes(&self) -> bool {
+        self.all_schemes
+    }
+
+    pub fn samples(&self) -> u8 {
+        self.samples
+    }
+}
@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tokio::time::{Instant, timeout_at};
+use url::Url;
+
+use super::timed_transport::TimedTransport;
+use crate::core::domain::{Proxy, ProxyScheme, Throughput};
+
+/// Downloads, and optionally uploads, a payload through a proxy to measure
+/// its transfer rate, within a byte budget shared by the whole run. Transfers
+/// go through the same transport as the judge requests.
+pub struct ThroughputProbe {
+    payload: u64,
+    download_url: Url,
+    upload_url: Option<Url>,
+    remaining: AtomicU64,
+    score: bool,
+}
+
+impl ThroughputProbe {
+    /// `budget` caps the bytes moved across all proxies, `None` meaning no
+    /// cap; once it is spent the stage is skipped. `score` lets the measured
+    /// rate count towards the score.
+    pub fn new(
+        payload: u64,
+        download_url: Url,
+        upload_url: Option<Url>,
+        budget: Option<u64>,
+        score: bool,
+    ) -> Self {
+        Self {
+            payload,
+            download_url,
+            upload_url,
+            remaining: AtomicU64::new(budget.unwrap_or(u64::MAX)),
+            score,
+        }
+    }
+
+    pub fn score(&self) -> bool {
+        self.score
+    }
+
+    /// `None` when the budget is spent or the download failed. Neither
+    /// transfer runs past `deadline`, the proxy's overall time budget.
+    pub async fn measure(
+        &self,
+        transport: &TimedTransport,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        deadline: Instant,
+    ) -> Option<Throughput> {
+        if deadline <= Instant::now() {
+            return None;
+        }
+
+        let planned = if self.upload_url.is_some() {
+            self.payload * 2
+        } else {
+            self.payload
+        };
+        if !self.reserve(planned) {
+            return None;
+        }
+
+        let downloaded = self.download(transport, proxy, scheme, deadline).await;
+        let upload_bps = match &self.upload_url {
+            Some(url) => self.upload(transport, proxy, scheme, url, deadline).await,
+            None => None,
+        };
+
+        // Give back what was reserved but not transferred.
+        let used =
+            downloaded.map_or(0, |(bytes, _)| bytes) + upload_bps.map_or(0, |_| self.payload);
+        self.remaining
+            .fetch_add(planned.saturating_sub(used), Ordering::Relaxed);
+
+        let (bytes, download_bps) = downloaded?;
+        Some(Throughput::new(bytes, download_bps, upload_bps))
+    }
+
+    fn reserve(&self, bytes: u64) -> bool {
+        self.remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(bytes)
+            })
+            .is_ok()
+    }
+
+    /// Timed from the response head to the last byte, so connection setup
+    /// does not count; a transfer cut short by the deadline still counts.
+    async fn download(
+        &self,
+        transport: &TimedTransport,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        deadline: Instant,
+    ) -> Option<(u64, u64)> {
+        let mut body = timeout_at(
+            deadline,
+            transport.download(proxy, scheme, &self.download_url),
+        )
+        .await
+        .ok()?
+        .ok()?;
+
+        let start = Instant::now();
+        let mut bytes = 0u64;
+        while bytes < self.payload {
+            match timeout_at(deadline, body.chunk()).await {
+                Ok(Ok(0)) | Err(_) => break,
+                Ok(Ok(read)) => bytes += read as u64,
+                Ok(Err(_)) if bytes > 0 => break,
+                Ok(Err(_)) => return None,
+            }
+        }
+        let bytes = bytes.min(self.payload);
+
+        (bytes > 0).then(|| (bytes, rate(bytes, start.elapsed())))
+    }
+
+    async fn upload(
+        &self,
+        transport: &TimedTransport,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        url: &Url,
+        deadline: Instant,
+    ) -> Option<u64> {
+        let start = Instant::now();
+        timeout_at(deadline, transport.upload(proxy, scheme, url, self.payload))
+            .await
+            .ok()?
+            .ok()?;
+
+        Some(rate(self.payload, start.elapsed()))
+    }
+}
+
+fn rate(bytes: u64, elapsed: Duration) -> u64 {
+    (bytes as u128 * 1000 / elapsed.as_millis().max(1)) as u64
+}
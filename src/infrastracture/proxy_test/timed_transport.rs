@@ -0,0 +1,829 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use rustls_platform_verifier::BuilderVerifierExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpStream, lookup_host};
+use tokio::time::{Instant, timeout};
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::{
+    ClientConfig, DigitallySignedStruct, SignatureScheme,
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{CryptoProvider, aws_lc_rs},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+};
+use tokio_socks::TargetAddr;
+use tokio_socks::tcp::{Socks4Stream, Socks5Stream};
+use url::Url;
+
+use super::failure;
+use crate::core::application::Error as AppError;
+use crate::core::domain::{Credentials, PhaseTimings, Proxy, ProxyScheme, error::ProxyTestError};
+
+const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
+/// Uploads are written from this one buffer rather than held in memory whole.
+static UPLOAD_CHUNK: [u8; 64 * 1024] = [0u8; 64 * 1024];
+
+trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+/// HTTP/1.1 through a proxy, driven by hand so every phase of a request can
+/// be timed on its own. Judge requests and the throughput transfers both go
+/// through here, so they share one TLS policy and one way of reaching a target.
+pub struct TimedTransport {
+    timeout: Duration,
+    proxy_tls: TlsConnector,
+    judge_tls: TlsConnector,
+}
+
+/// A connection through the proxy, ready for a request to its target.
+struct Connection {
+    stream: Box<dyn Stream>,
+    /// Plain HTTP through an HTTP(S) proxy is forwarded, everything else is tunnelled.
+    forwarded: bool,
+    start: Instant,
+    connect: Duration,
+    handshake: Duration,
+    tls: Duration,
+}
+
+/// The body of a response, read as it arrives.
+pub struct ResponseBody {
+    stream: Box<dyn Stream>,
+    buf: Vec<u8>,
+    /// Bytes that arrived along with the response head.
+    pending: usize,
+}
+
+impl TimedTransport {
+    /// `accept_invalid_certs` only applies to judges; the TLS hop to an HTTPS
+    /// proxy is always verified. Fails when the platform verifier is unavailable.
+    pub fn new(timeout: Duration, accept_invalid_certs: bool) -> Result<Self, AppError> {
+        let verified = tls_connector(false)?;
+
+        Ok(Self {
+            timeout,
+            judge_tls: if accept_invalid_certs {
+                tls_connector(true)?
+            } else {
+                verified.clone()
+            },
+            proxy_tls: verified,
+        })
+    }
+
+    /// Returns the response body and the timings of the request.
+    pub async fn get(
+        &self,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        url: &Url,
+    ) -> Result<(String, PhaseTimings), ProxyTestError> {
+        timeout(self.timeout, self.request(proxy, scheme, url))
+            .await
+            .map_err(|_| ProxyTestError::Timeout)?
+    }
+
+    /// Sends a GET and returns once the response head is in; the body is left
+    /// to the caller. Only getting that far is bound by the timeout.
+    pub async fn download(
+        &self,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        url: &Url,
+    ) -> Result<ResponseBody, ProxyTestError> {
+        timeout(self.timeout, async {
+            let mut connection = self.connect(proxy, scheme, url).await?;
+            let head = request_head("GET", url, connection.forwarded, proxy, "");
+            write(&mut connection.stream, head.as_bytes()).await?;
+            let buf = read_head(&mut connection.stream, connection.forwarded).await?;
+
+            Ok(ResponseBody {
+                pending: buf.len(),
+                stream: connection.stream,
+                buf,
+            })
+        })
+        .await
+        .map_err(|_| ProxyTestError::Timeout)?
+    }
+
+    /// POSTs `length` zero bytes and waits for a successful response head.
+    /// Only the connection is bound by the timeout, the transfer is the
+    /// caller's to limit.
+    pub async fn upload(
+        &self,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        url: &Url,
+        mut length: u64,
+    ) -> Result<(), ProxyTestError> {
+        let mut connection = timeout(self.timeout, self.connect(proxy, scheme, url))
+            .await
+            .map_err(|_| ProxyTestError::Timeout)??;
+
+        let head = request_head(
+            "POST",
+            url,
+            connection.forwarded,
+            proxy,
+            &format!(
+                "Content-Type: application/octet-stream\r\nContent-Length: {}\r\n",
+                length
+            ),
+        );
+        write(&mut connection.stream, head.as_bytes()).await?;
+        while length > 0 {
+            let chunk = length.min(UPLOAD_CHUNK.len() as u64) as usize;
+            write(&mut connection.stream, &UPLOAD_CHUNK[..chunk]).await?;
+            length -= chunk as u64;
+        }
+        read_head(&mut connection.stream, connection.forwarded).await?;
+
+        Ok(())
+    }
+
+    async fn request(
+        &self,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        url: &Url,
+    ) -> Result<(String, PhaseTimings), ProxyTestError> {
+        let mut connection = self.connect(proxy, scheme, url).await?;
+        let head = request_head("GET", url, connection.forwarded, proxy, "");
+        write(&mut connection.stream, head.as_bytes()).await?;
+
+        let phase = Instant::now();
+        let mut buf = vec![0u8; 8 * 1024];
+        let read = connection
+            .stream
+            .read(&mut buf)
+            .await
+            .map_err(|e| failure::from_io(&e))?;
+        let ttfb = phase.elapsed();
+        buf.truncate(read);
+
+        let body = read_body(&mut connection.stream, buf, connection.forwarded).await?;
+        let timings = PhaseTimings::new(
+            connection.connect,
+            connection.handshake,
+            connection.tls,
+            ttfb,
+            connection.start.elapsed(),
+        );
+
+        Ok((body, timings))
+    }
+
+    /// Opens the connection to the proxy and, unless the request is forwarded,
+    /// the tunnel to `url`'s host, timing each step.
+    async fn connect(
+        &self,
+        proxy: &Proxy,
+        scheme: &ProxyScheme,
+        url: &Url,
+    ) -> Result<Connection, ProxyTestError> {
+        let host = url.host_str().ok_or(ProxyTestError::InvalidResponse)?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = url.port_or_known_default().unwrap_or(80);
+        let https = url.scheme() == "https";
+        let credentials = proxy.credentials().as_ref();
+        let forwarded = !https && matches!(scheme, ProxyScheme::Http | ProxyScheme::Https);
+
+        let start = Instant::now();
+        let tcp = TcpStream::connect(format!("{}:{}", proxy.ip(), proxy.port()))
+            .await
+            .map_err(|e| failure::from_io(&e))?;
+        let connect = start.elapsed();
+
+        let phase = Instant::now();
+        let mut stream: Box<dyn Stream> = match scheme {
+            ProxyScheme::Http | ProxyScheme::Https => {
+                let mut stream: Box<dyn Stream> = if *scheme == ProxyScheme::Https {
+                    let proxy_host = proxy.ip().to_string();
+                    let proxy_host = proxy_host.trim_start_matches('[').trim_end_matches(']');
+                    Box::new(tls_connect(&self.proxy_tls, proxy_host, tcp).await?)
+                } else {
+                    Box::new(tcp)
+                };
+                if !forwarded {
+                    connect_tunnel(&mut stream, host, port, credentials).await?;
+                }
+                stream
+            }
+            ProxyScheme::Socks5h => {
+                let target = TargetAddr::Domain(host.to_string().into(), port);
+                Box::new(socks5(tcp, target, credentials).await?)
+            }
+            ProxyScheme::Socks5 => {
+                let target = TargetAddr::Ip(resolve(host, port, false).await?);
+                Box::new(socks5(tcp, target, credentials).await?)
+            }
+            ProxyScheme::Socks4 => {
+                let target = TargetAddr::Ip(resolve(host, port, true).await?);
+                let result = match credentials {
+                    Some(c) => {
+                        Socks4Stream::connect_with_userid_and_socket(tcp, target, &c.username).await
+                    }
+                    None => Socks4Stream::connect_with_socket(tcp, target).await,
+                };
+                Box::new(result.map_err(failure::from_socks)?)
+            }
+        };
+        let handshake = phase.elapsed();
+
+        let phase = Instant::now();
+        if https {
+            stream = Box::new(tls_connect(&self.judge_tls, host, stream).await?);
+        }
+        let tls = phase.elapsed();
+
+        Ok(Connection {
+            stream,
+            forwarded,
+            start,
+            connect,
+            handshake,
+            tls,
+        })
+    }
+}
+
+impl ResponseBody {
+    /// Size of the next piece of the body, `0` once the connection is closed.
+    pub async fn chunk(&mut self) -> Result<usize, ProxyTestError> {
+        if self.pending > 0 {
+            return Ok(std::mem::take(&mut self.pending));
+        }
+
+        self.buf.resize(64 * 1024, 0);
+        self.stream
+            .read(&mut self.buf)
+            .await
+            .map_err(|e| failure::from_io(&e))
+    }
+}
+
+/// `extra` holds any further header lines, each ending in CRLF.
+fn request_head(method: &str, url: &Url, forwarded: bool, proxy: &Proxy, extra: &str) -> String {
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nAccept: */*\r\nConnection: close\r\n{}",
+        method,
+        if forwarded {
+            url.as_str()
+        } else {
+            &url[url::Position::BeforePath..]
+        },
+        &url[url::Position::BeforeHost..url::Position::AfterPort],
+        extra
+    );
+    if forwarded && let Some(c) = proxy.credentials() {
+        head.push_str(&proxy_authorization(c));
+    }
+    head.push_str("\r\n");
+
+    head
+}
+
+async fn write(stream: &mut Box<dyn Stream>, bytes: &[u8]) -> Result<(), ProxyTestError> {
+    stream
+        .write_all(bytes)
+        .await
+        .map_err(|e| failure::from_io(&e))
+}
+
+/// Reads up to the end of a successful response head and returns whatever
+/// part of the body arrived with it.
+async fn read_head(
+    stream: &mut Box<dyn Stream>,
+    forwarded: bool,
+) -> Result<Vec<u8>, ProxyTestError> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 8 * 1024];
+
+    loop {
+        let mut headers = [httparse::EMPTY_HEADER; 64];
+        let mut response = httparse::Response::new(&mut headers);
+        match response.parse(&buf) {
+            Ok(httparse::Status::Complete(offset)) => {
+                if let Some(code) = response.code.filter(|code| !is_success(*code)) {
+                    return Err(status_error(code, forwarded));
+                }
+                return Ok(buf.split_off(offset));
+            }
+            Ok(httparse::Status::Partial) if buf.len() <= MAX_RESPONSE_SIZE => {}
+            _ => return Err(malformed_error(forwarded)),
+        }
+
+        match stream.read(&mut chunk).await {
+            Ok(0) => return Err(malformed_error(forwarded)),
+            Ok(read) => buf.extend_from_slice(&chunk[..read]),
+            Err(e) => return Err(failure::from_io(&e)),
+        }
+    }
+}
+
+/// Verifies against the platform's roots, or accepts any certificate with
+/// `accept_invalid_certs`; verification is never dropped otherwise.
+fn tls_connector(accept_invalid_certs: bool) -> Result<TlsConnector, AppError> {
+    let provider = Arc::new(aws_lc_rs::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AppError::External(format!("Invalid TLS configuration: {}", e)))?;
+
+    let config = if accept_invalid_certs {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification(provider)))
+            .with_no_client_auth()
+    } else {
+        builder
+            .with_platform_verifier()
+            .map_err(|e| {
+                AppError::External(format!(
+                    "Failed to load the platform certificate verifier: {}",
+                    e
+                ))
+            })?
+            .with_no_client_auth()
+    };
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+async fn tls_connect<S: Stream + 'static>(
+    tls: &TlsConnector,
+    host: &str,
+    stream: S,
+) -> Result<impl Stream + use<S>, ProxyTestError> {
+    let name =
+        ServerName::try_from(host.to_string()).map_err(|_| ProxyTestError::ConnectionFailed)?;
+
+    tls.connect(name, stream)
+        .await
+        .map_err(|e| failure::from_io(&e))
+}
+
+async fn socks5(
+    tcp: TcpStream,
+    target: TargetAddr<'static>,
+    credentials: Option<&Credentials>,
+) -> Result<Socks5Stream<TcpStream>, ProxyTestError> {
+    match credentials {
+        Some(c) => {
+            Socks5Stream::connect_with_password_and_socket(tcp, target, &c.username, &c.password)
+                .await
+        }
+        None => Socks5Stream::connect_with_socket(tcp, target).await,
+    }
+    .map_err(failure::from_socks)
+}
+
+/// Local resolution for socks5 and socks4; SOCKS4 can only address IPv4.
+async fn resolve(host: &str, port: u16, ipv4_only: bool) -> Result<SocketAddr, ProxyTestError> {
+    lookup_host((host, port))
+        .await
+        .map_err(|_| ProxyTestError::JudgeUnreachable)?
+        .find(|addr| !ipv4_only || addr.is_ipv4())
+        .ok_or(ProxyTestError::JudgeUnreachable)
+}
+
+fn proxy_authorization(credentials: &Credentials) -> String {
+    format!(
+        "Proxy-Authorization: Basic {}\r\n",
+        BASE64.encode(format!("{}:{}", credentials.username, credentials.password))
+    )
+}
+
+async fn connect_tunnel(
+    stream: &mut Box<dyn Stream>,
+    host: &str,
+    port: u16,
+    credentials: Option<&Credentials>,
+) -> Result<(), ProxyTestError> {
+    let authority = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    let mut request = format!(
+        "CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n",
+        authority = authority
+    );
+    if let Some(c) = credentials {
+        request.push_str(&proxy_authorization(c));
+    }
+    request.push_str("\r\n");
+
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| failure::from_io(&e))?;
+
+    // Read byte by byte so nothing after the reply head is consumed.
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let byte = stream.read_u8().await.map_err(|e| failure::from_io(&e))?;
+        head.push(byte);
+        if head.len() > MAX_RESPONSE_SIZE {
+            return Err(ProxyTestError::NotAProxy);
+        }
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; 32];
+    let mut response = httparse::Response::new(&mut headers);
+    match response.parse(&head) {
+        Ok(httparse::Status::Complete(_)) => match response.code {
+            Some(code) if !is_success(code) => Err(failure::from_proxy_status(code)),
+            _ => Ok(()),
+        },
+        _ => Err(ProxyTestError::NotAProxy),
+    }
+}
+
+/// Reads until the body announced by the response head is complete; falls
+/// back to EOF when neither a length nor chunked encoding is given.
+/// A `forwarded` response comes from the proxy itself, so its errors are the proxy's.
+async fn read_body(
+    stream: &mut Box<dyn Stream>,
+    mut buf: Vec<u8>,
+    forwarded: bool,
+) -> Result<String, ProxyTestError> {
+    let mut chunk = [0u8; 8 * 1024];
+
+    loop {
+        if let Some(body) = parse_response(&buf, forwarded)? {
+            return Ok(body);
+        }
+        if buf.len() > MAX_RESPONSE_SIZE {
+            return Err(ProxyTestError::InvalidResponse);
+        }
+
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => buf.extend_from_slice(&chunk[..read]),
+        }
+    }
+
+    // EOF: whatever arrived is the body, as long as the head was complete.
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut response = httparse::Response::new(&mut headers);
+    match response.parse(&buf) {
+        Ok(httparse::Status::Complete(offset)) => match response.code {
+            Some(code) if !is_success(code) => Err(status_error(code, forwarded)),
+            _ => Ok(String::from_utf8_lossy(&buf[offset..]).into_owned()),
+        },
+        _ => Err(malformed_error(forwarded)),
+    }
+}
+
+/// `Ok(None)` while more bytes are needed.
+fn parse_response(buf: &[u8], forwarded: bool) -> Result<Option<String>, ProxyTestError> {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut response = httparse::Response::new(&mut headers);
+
+    let offset = match response.parse(buf) {
+        Ok(httparse::Status::Complete(offset)) => offset,
+        Ok(httparse::Status::Partial) => return Ok(None),
+        Err(_) => return Err(malformed_error(forwarded)),
+    };
+    if let Some(code) = response.code.filter(|code| !is_success(*code)) {
+        return Err(status_error(code, forwarded));
+    }
+
+    let header = |name: &str| {
+        response
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| String::from_utf8_lossy(h.value).trim().to_lowercase())
+    };
+    let body = &buf[offset..];
+
+    if header("transfer-encoding").is_some_and(|te| te.contains("chunked")) {
+        return Ok(decode_chunked(body).map(|b| String::from_utf8_lossy(&b).into_owned()));
+    }
+
+    match header("content-length").and_then(|len| len.parse::<usize>().ok()) {
+        Some(len) if body.len() >= len => {
+            Ok(Some(String::from_utf8_lossy(&body[..len]).into_owned()))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn is_success(code: u16) -> bool {
+    (200..300).contains(&code)
+}
+
+/// Only a forwarding proxy answers for itself; through a tunnel the status is the judge's.
+fn status_error(code: u16, forwarded: bool) -> ProxyTestError {
+    match code {
+        407 | 403 | 500..=599 if forwarded => failure::from_proxy_status(code),
+        _ => ProxyTestError::InvalidResponse,
+    }
+}
+
+fn malformed_error(forwarded: bool) -> ProxyTestError {
+    if forwarded {
+        ProxyTestError::NotAProxy
+    } else {
+        ProxyTestError::InvalidResponse
+    }
+}
+
+/// `None` until the terminating zero-size chunk has arrived.
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+
+        if size == 0 {
+            return Some(decoded);
+        }
+        if body.len() < size + 2 {
+            return None;
+        }
+
+        decoded.extend_from_slice(&body[..size]);
+        body = &body[size + 2..];
+    }
+}
+
+/// Accepts any certificate, for self-signed judges.
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastracture::judge_server::load_tls_config;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    fn body(response: &[u8], forwarded: bool) -> Result<Option<String>, ProxyTestError> {
+        parse_response(response, forwarded)
+    }
+
+    fn proxy_at(addr: SocketAddr, scheme: ProxyScheme) -> Proxy {
+        Proxy::from_str(&format!("{}://{}", scheme, addr)).unwrap()
+    }
+
+    /// Answers every request with `ok` over TLS with a self-signed certificate.
+    async fn self_signed_server() -> SocketAddr {
+        let acceptor = TlsAcceptor::from(load_tls_config(None, &["127.0.0.1".into()]).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    let Ok(mut tls) = acceptor.accept(tcp).await else {
+                        return;
+                    };
+                    let mut buf = [0u8; 4096];
+                    let _ = tls.read(&mut buf).await;
+                    let _ = tls
+                        .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok")
+                        .await;
+                    let _ = tls.shutdown().await;
+                });
+            }
+        });
+
+        addr
+    }
+
+    /// A plain HTTP proxy that only tunnels CONNECT requests.
+    async fn tunnel_proxy() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut head = Vec::new();
+                    while !head.ends_with(b"\r\n\r\n") {
+                        let Ok(byte) = client.read_u8().await else {
+                            return;
+                        };
+                        head.push(byte);
+                    }
+                    let head = String::from_utf8_lossy(&head);
+                    let target = head.split_whitespace().nth(1).unwrap_or_default();
+                    let Ok(mut upstream) = TcpStream::connect(target).await else {
+                        return;
+                    };
+                    let _ = client
+                        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                        .await;
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                });
+            }
+        });
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn judge_insecure_never_skips_verifying_an_https_proxy() {
+        let transport = TimedTransport::new(Duration::from_secs(5), true).unwrap();
+        let proxy = proxy_at(self_signed_server().await, ProxyScheme::Https);
+        let url = Url::parse("http://judge.invalid/").unwrap();
+
+        assert!(
+            transport
+                .get(&proxy, &ProxyScheme::Https, &url)
+                .await
+                .is_err()
+        );
+        // The throughput transfers take the same route as the judge requests.
+        assert!(
+            transport
+                .download(&proxy, &ProxyScheme::Https, &url)
+                .await
+                .is_err()
+        );
+        assert!(
+            transport
+                .upload(&proxy, &ProxyScheme::Https, &url, 7)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn judge_insecure_only_relaxes_the_judge_hop() {
+        let proxy = proxy_at(tunnel_proxy().await, ProxyScheme::Http);
+        let url = Url::parse(&format!("https://{}/ip", self_signed_server().await)).unwrap();
+
+        let verified = TimedTransport::new(Duration::from_secs(5), false).unwrap();
+        assert!(
+            verified
+                .get(&proxy, &ProxyScheme::Http, &url)
+                .await
+                .is_err()
+        );
+        assert!(
+            verified
+                .download(&proxy, &ProxyScheme::Http, &url)
+                .await
+                .is_err()
+        );
+
+        let insecure = TimedTransport::new(Duration::from_secs(5), true).unwrap();
+        let (body, _) = insecure
+            .get(&proxy, &ProxyScheme::Http, &url)
+            .await
+            .unwrap();
+        assert_eq!(body, "ok");
+        let mut download = insecure
+            .download(&proxy, &ProxyScheme::Http, &url)
+            .await
+            .unwrap();
+        assert_eq!(download.chunk().await, Ok(2));
+        assert_eq!(
+            insecure.upload(&proxy, &ProxyScheme::Http, &url, 7).await,
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn decodes_complete_chunks() {
+        assert_eq!(
+            decode_chunked(b"4\r\nWiki\r\n5\r\npedia\r\n0\r\n\r\n"),
+            Some(b"Wikipedia".to_vec())
+        );
+        assert_eq!(decode_chunked(b"0\r\n\r\n"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn ignores_chunk_extensions() {
+        assert_eq!(
+            decode_chunked(b"4;name=value\r\nWiki\r\nA ; x\r\n0123456789\r\n0;last\r\n\r\n"),
+            Some(b"Wiki0123456789".to_vec())
+        );
+    }
+
+    #[test]
+    fn waits_for_truncated_chunks() {
+        assert_eq!(decode_chunked(b""), None);
+        assert_eq!(decode_chunked(b"4\r\nWi"), None);
+        assert_eq!(decode_chunked(b"4\r\nWiki"), None);
+        assert_eq!(decode_chunked(b"4\r\nWiki\r\n"), None);
+        assert_eq!(decode_chunked(b"4\r\nWiki\r\n0"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_chunk_sizes() {
+        assert_eq!(decode_chunked(b"zz\r\nWiki\r\n0\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn reads_a_body_up_to_its_content_length() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello, trailing bytes";
+        assert_eq!(body(response, false), Ok(Some("hello".to_string())));
+
+        let partial = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhel";
+        assert_eq!(body(partial, false), Ok(None));
+    }
+
+    #[test]
+    fn reads_a_chunked_body() {
+        let response =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2;x=y\r\nok\r\n0\r\n\r\n";
+        assert_eq!(body(response, false), Ok(Some("ok".to_string())));
+
+        let truncated = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\no";
+        assert_eq!(body(truncated, false), Ok(None));
+    }
+
+    #[test]
+    fn waits_for_eof_without_a_content_length() {
+        assert_eq!(body(b"HTTP/1.1 200 OK\r\n\r\nhello", false), Ok(None));
+        assert_eq!(body(b"HTTP/1.1 200 OK\r\nContent-Len", false), Ok(None));
+    }
+
+    #[tokio::test]
+    async fn takes_the_body_up_to_eof_without_a_content_length() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        server
+            .write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        server.write_all(b"203.0.113.7").await.unwrap();
+        drop(server);
+
+        let mut stream: Box<dyn Stream> = Box::new(client);
+        assert_eq!(
+            read_body(&mut stream, Vec::new(), false).await,
+            Ok("203.0.113.7".to_string())
+        );
+    }
+
+    #[test]
+    fn maps_statuses_by_who_answered() {
+        let forbidden = b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n";
+        assert_eq!(
+            body(forbidden, true),
+            Err(ProxyTestError::ProxyRejected("HTTP 403".to_string()))
+        );
+        assert_eq!(body(forbidden, false), Err(ProxyTestError::InvalidResponse));
+
+        let auth = b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n";
+        assert_eq!(body(auth, true), Err(ProxyTestError::AuthRequired));
+
+        let not_found = b"HTTP/1.1 404 Not Found\r\n\r\n";
+        assert_eq!(body(not_found, true), Err(ProxyTestError::InvalidResponse));
+    }
+
+    #[test]
+    fn maps_garbage_by_who_answered() {
+        let garbage = b"SSH-2.0-OpenSSH_9.6\r\n";
+        assert_eq!(body(garbage, true), Err(ProxyTestError::NotAProxy));
+        assert_eq!(body(garbage, false), Err(ProxyTestError::InvalidResponse));
+    }
+}
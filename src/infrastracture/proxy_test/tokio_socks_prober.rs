@@ -0,0 +1,349 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, lookup_host};
+use tokio::sync::OnceCell;
+use tokio::time::timeout;
+use tokio_socks::Error as SocksError;
+use tokio_socks::TargetAddr;
+use tokio_socks::tcp::{Socks4Stream, Socks5Stream};
+use url::{Host, Url};
+
+use crate::core::{
+    application::ports::SocksProbePort,
+    domain::{
+        Proxy, ProxyScheme, SocksAuthMethod, SocksProbe, SocksReply, SocksVersion,
+        error::SocksProbeError,
+    },
+};
+
+const SOCKS5_NO_AUTH: u8 = 0x00;
+const SOCKS5_USERNAME_PASSWORD: u8 = 0x02;
+
+/// Talks SOCKS4/4a/5 to the proxy directly instead of going through reqwest,
+/// asking it to CONNECT to the judge host once by IP and once by name.
+pub struct TokioSocksProber {
+    timeout: Duration,
+    target_host: Host,
+    target_port: u16,
+    resolved: OnceCell<Option<SocketAddr>>,
+}
+
+impl TokioSocksProber {
+    pub fn new(timeout: u8, target: &Url) -> Self {
+        Self {
+            timeout: Duration::from_secs(timeout as u64),
+            target_host: target
+                .host()
+                .map_or(Host::Domain(String::new()), |h| h.to_owned()),
+            target_port: target.port_or_known_default().unwrap_or(80),
+            resolved: OnceCell::new(),
+        }
+    }
+
+    /// Prefers IPv4 so the same address also works for SOCKS4.
+    async fn ip_target(&self) -> Option<SocketAddr> {
+        *self
+            .resolved
+            .get_or_init(|| async {
+                match &self.target_host {
+                    Host::Ipv4(ip) => Some(SocketAddr::new((*ip).into(), self.target_port)),
+                    Host::Ipv6(ip) => Some(SocketAddr::new((*ip).into(), self.target_port)),
+                    Host::Domain(name) => {
+                        let addrs: Vec<SocketAddr> =
+                            timeout(self.timeout, lookup_host((name.as_str(), self.target_port)))
+                                .await
+                                .ok()?
+                                .ok()?
+                                .collect();
+                        addrs
+                            .iter()
+                            .find(|addr| addr.is_ipv4())
+                            .or_else(|| addrs.first())
+                            .copied()
+                    }
+                }
+            })
+            .await
+    }
+
+    fn domain_target(&self) -> Option<TargetAddr<'static>> {
+        match &self.target_host {
+            Host::Domain(name) if !name.is_empty() => {
+                Some(TargetAddr::Domain(name.clone().into(), self.target_port))
+            }
+            _ => None,
+        }
+    }
+
+    async fn connect(&self, proxy: &Proxy) -> Result<TcpStream, SocksProbeError> {
+        let addr = format!("{}:{}", proxy.ip(), proxy.port());
+
+        timeout(self.timeout, TcpStream::connect(addr))
+            .await
+            .ok()
+            .and_then(Result::ok)
+            .ok_or(SocksProbeError::Unreachable)
+    }
+
+    /// Greets with a single method; the server echoes it back only if it accepts it.
+    async fn offers_method(&self, proxy: &Proxy, method: u8) -> Result<bool, SocksProbeError> {
+        let mut stream = self.connect(proxy).await?;
+
+        let greeting = async {
+            stream.write_all(&[0x05, 0x01, method]).await?;
+            let mut reply = [0u8; 2];
+            stream.read_exact(&mut reply).await?;
+            Ok::<_, std::io::Error>(reply)
+        };
+
+        match timeout(self.timeout, greeting).await {
+            Ok(Ok([0x05, chosen])) => Ok(chosen == method),
+            _ => Err(SocksProbeError::NotSocks),
+        }
+    }
+
+    async fn connect_target(
+        &self,
+        proxy: &Proxy,
+        version: SocksVersion,
+        target: TargetAddr<'static>,
+    ) -> Result<SocksReply, SocksProbeError> {
+        let stream = self.connect(proxy).await?;
+        let credentials = proxy.credentials().as_ref();
+
+        let handshake = async {
+            match (version, credentials) {
+                (SocksVersion::Socks5, Some(c)) => Socks5Stream::connect_with_password_and_socket(
+                    stream,
+                    target,
+                    &c.username,
+                    &c.password,
+                )
+                .await
+                .map(drop),
+                (SocksVersion::Socks5, None) => Socks5Stream::connect_with_socket(stream, target)
+                    .await
+                    .map(drop),
+                (SocksVersion::Socks4, Some(c)) => {
+                    Socks4Stream::connect_with_userid_and_socket(stream, target, &c.username)
+                        .await
+                        .map(drop)
+                }
+                (SocksVersion::Socks4, None) => Socks4Stream::connect_with_socket(stream, target)
+                    .await
+                    .map(drop),
+            }
+        };
+
+        match timeout(self.timeout, handshake).await {
+            Ok(result) => to_reply(version, result),
+            Err(_) => Ok(SocksReply::NoReply),
+        }
+    }
+
+    async fn probe_version(
+        &self,
+        proxy: &Proxy,
+        version: SocksVersion,
+    ) -> Result<SocksProbe, SocksProbeError> {
+        let auth_methods = match version {
+            SocksVersion::Socks5 => {
+                let (no_auth, user_pass) = tokio::try_join!(
+                    self.offers_method(proxy, SOCKS5_NO_AUTH),
+                    self.offers_method(proxy, SOCKS5_USERNAME_PASSWORD)
+                )?;
+                [
+                    (no_auth, SocksAuthMethod::NoAuth),
+                    (user_pass, SocksAuthMethod::UsernamePassword),
+                ]
+                .into_iter()
+                .filter_map(|(offered, method)| offered.then_some(method))
+                .collect()
+            }
+            SocksVersion::Socks4 => Vec::new(),
+        };
+
+        let ip_target = self
+            .ip_target()
+            .await
+            .filter(|addr| version == SocksVersion::Socks5 || addr.is_ipv4());
+
+        let ip_reply = optional(
+            ip_target.map(|addr| self.connect_target(proxy, version, TargetAddr::Ip(addr))),
+        )
+        .await?;
+        let domain_reply = optional(
+            self.domain_target()
+                .map(|target| self.connect_target(proxy, version, target)),
+        )
+        .await?;
+
+        // SOCKS4 has no greeting, an unanswered request is our only evidence.
+        if version == SocksVersion::Socks4
+            && [ip_reply, domain_reply]
+                .iter()
+                .flatten()
+                .all(|reply| *reply == SocksReply::NoReply)
+        {
+            return Err(SocksProbeError::NotSocks);
+        }
+
+        Ok(SocksProbe::new(
+            version,
+            auth_methods,
+            ip_reply,
+            domain_reply,
+        ))
+    }
+}
+
+async fn optional<F, T, E>(future: Option<F>) -> Result<Option<T>, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    match future {
+        Some(future) => future.await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Recovers the reply code tokio-socks decoded into an error variant.
+fn to_reply(
+    version: SocksVersion,
+    result: Result<(), SocksError>,
+) -> Result<SocksReply, SocksProbeError> {
+    let reply = match result {
+        Ok(()) => SocksReply::Granted,
+        Err(SocksError::GeneralSocksServerFailure) => match version {
+            SocksVersion::Socks4 => SocksReply::Refused(0x5B),
+            SocksVersion::Socks5 => SocksReply::Refused(0x01),
+        },
+        Err(SocksError::ConnectionNotAllowedByRuleset) => SocksReply::Refused(0x02),
+        Err(SocksError::NetworkUnreachable) => SocksReply::Refused(0x03),
+        Err(SocksError::HostUnreachable) => SocksReply::Refused(0x04),
+        Err(SocksError::ConnectionRefused) => SocksReply::Refused(0x05),
+        Err(SocksError::TtlExpired) => SocksReply::Refused(0x06),
+        Err(SocksError::CommandNotSupported) => SocksReply::Refused(0x07),
+        Err(SocksError::AddressTypeNotSupported) => SocksReply::Refused(0x08),
+        Err(SocksError::IdentdAuthFailure) => SocksReply::Refused(0x5C),
+        Err(SocksError::InvalidUserIdAuthFailure) => SocksReply::Refused(0x5D),
+        // tokio-socks reports unknown SOCKS5 reply codes as an unknown auth method.
+        Err(SocksError::UnknownError | SocksError::UnknownAuthMethod) => SocksReply::Unknown,
+        Err(SocksError::NoAcceptableAuthMethods | SocksError::AuthorizationRequired) => {
+            SocksReply::AuthRequired
+        }
+        Err(SocksError::PasswordAuthFailure(status)) => SocksReply::AuthFailed(status),
+        Err(SocksError::Io(_)) => SocksReply::NoReply,
+        Err(_) => return Err(SocksProbeError::NotSocks),
+    };
+
+    Ok(reply)
+}
+
+#[async_trait]
+impl SocksProbePort for TokioSocksProber {
+    async fn probe(&self, proxy: &Proxy) -> Result<SocksProbe, SocksProbeError> {
+        match proxy.scheme() {
+            Some(ProxyScheme::Socks4) => self.probe_version(proxy, SocksVersion::Socks4).await,
+            Some(_) => self.probe_version(proxy, SocksVersion::Socks5).await,
+            None => match self.probe_version(proxy, SocksVersion::Socks5).await {
+                Err(SocksProbeError::NotSocks) => {
+                    self.probe_version(proxy, SocksVersion::Socks4).await
+                }
+                result => result,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::domain::error::ProxyTestError;
+
+    fn reply(version: SocksVersion, error: SocksError) -> SocksReply {
+        to_reply(version, Err(error)).unwrap()
+    }
+
+    #[test]
+    fn decoded_errors_map_back_to_reply_codes() {
+        assert_eq!(
+            to_reply(SocksVersion::Socks5, Ok(())).unwrap(),
+            SocksReply::Granted
+        );
+        assert_eq!(
+            reply(SocksVersion::Socks5, SocksError::GeneralSocksServerFailure),
+            SocksReply::Refused(0x01)
+        );
+        assert_eq!(
+            reply(SocksVersion::Socks4, SocksError::GeneralSocksServerFailure),
+            SocksReply::Refused(0x5B)
+        );
+        assert_eq!(
+            reply(
+                SocksVersion::Socks5,
+                SocksError::ConnectionNotAllowedByRuleset
+            ),
+            SocksReply::Refused(0x02)
+        );
+        assert_eq!(
+            reply(SocksVersion::Socks4, SocksError::InvalidUserIdAuthFailure),
+            SocksReply::Refused(0x5D)
+        );
+        assert_eq!(
+            reply(SocksVersion::Socks5, SocksError::PasswordAuthFailure(0x01)),
+            SocksReply::AuthFailed(0x01)
+        );
+        assert_eq!(
+            reply(
+                SocksVersion::Socks5,
+                SocksError::Io(std::io::ErrorKind::UnexpectedEof.into())
+            ),
+            SocksReply::NoReply
+        );
+        assert!(matches!(
+            to_reply(
+                SocksVersion::Socks5,
+                Err(SocksError::InvalidResponseVersion)
+            ),
+            Err(SocksProbeError::NotSocks)
+        ));
+    }
+
+    #[test]
+    fn refused_replies_become_failure_kinds() {
+        assert_eq!(
+            SocksReply::Refused(0x02).failure(),
+            Some(ProxyTestError::ProxyRejected(
+                "SOCKS refused 0x02 (not allowed by ruleset)".to_string()
+            ))
+        );
+        assert_eq!(
+            SocksReply::Refused(0x04).failure(),
+            Some(ProxyTestError::JudgeUnreachable)
+        );
+        assert_eq!(
+            SocksReply::AuthFailed(0x01).failure(),
+            Some(ProxyTestError::AuthRequired)
+        );
+        assert_eq!(SocksReply::Granted.failure(), None);
+        assert_eq!(SocksReply::NoReply.failure(), None);
+    }
+
+    #[test]
+    fn a_granted_target_keeps_the_probe_from_failing() {
+        let refused = Some(SocksReply::Refused(0x02));
+        let probe = |ip, domain| SocksProbe::new(SocksVersion::Socks5, Vec::new(), ip, domain);
+
+        assert_eq!(probe(refused, Some(SocksReply::Granted)).failure(), None);
+        assert_eq!(
+            probe(Some(SocksReply::NoReply), refused).failure(),
+            refused.and_then(|r| r.failure())
+        );
+        assert_eq!(probe(None, None).failure(), None);
+    }
+}
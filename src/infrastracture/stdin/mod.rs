@@ -0,0 +1,3 @@
+mod stdin_proxy_repository;
+
+pub use stdin_proxy_repository::StdinProxyRepository;
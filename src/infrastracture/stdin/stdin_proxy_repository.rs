@@ -0,0 +1,33 @@
+use crate::core::application::{Error as AppError, dto::InputFormat, ports::ProxyRepository};
+use crate::core::domain::{Proxy, RejectedLine};
+use crate::infrastracture::parser::forward_lines;
+use async_trait::async_trait;
+use tokio::io::{BufReader, stdin};
+use tokio::sync::mpsc;
+
+pub struct StdinProxyRepository {
+    format: InputFormat,
+}
+
+impl StdinProxyRepository {
+    pub fn new(format: InputFormat) -> Self {
+        Self { format }
+    }
+}
+
+#[async_trait]
+impl ProxyRepository for StdinProxyRepository {
+    async fn stream_proxies(
+        &self,
+    ) -> Result<mpsc::Receiver<Result<Proxy, RejectedLine>>, AppError> {
+        let (tx, rx) = mpsc::channel::<Result<Proxy, RejectedLine>>(100);
+        let format = self.format;
+
+        tokio::spawn(async move {
+            let source = "stdin".to_string();
+            forward_lines(BufReader::new(stdin()), source, format, tx).await;
+        });
+
+        Ok(rx)
+    }
+}
@@ -1,8 +1,7 @@
 use crate::core::application::{Error as AppError, ports::ProxyRepository};
-use crate::core::domain::Proxy;
+use crate::core::domain::{Proxy, RejectedLine};
 use async_trait::async_trait;
-use std::sync::Arc;
-use tokio::sync::{Semaphore, mpsc};
+use tokio::sync::mpsc;
 
 pub struct StringProxyRepository {
     proxies: Vec<String>,
@@ -22,21 +21,16 @@ impl StringProxyRepository {
 
 #[async_trait]
 impl ProxyRepository for StringProxyRepository {
-    async fn stream_proxies(&self, max_worker: usize) -> Result<mpsc::Receiver<Proxy>, AppError> {
-        let (tx, rx) = mpsc::channel::<Proxy>(self.proxies.len());
-        let semaphore = Arc::new(Semaphore::new(max_worker));
+    async fn stream_proxies(
+        &self,
+    ) -> Result<mpsc::Receiver<Result<Proxy, RejectedLine>>, AppError> {
+        let (tx, rx) = mpsc::channel::<Result<Proxy, RejectedLine>>(self.proxies.len().max(1));
 
-        for line in self.proxies.clone() {
-            if let Ok(proxy) = Proxy::from_str(&line) {
-                let tx_clone = tx.clone();
-                let sem_clone = semaphore.clone();
-
-                tokio::spawn(async move {
-                    let permit = sem_clone.acquire_owned().await.unwrap();
-                    let _ = tx_clone.send(proxy).await;
-                    drop(permit);
-                });
-            }
+        // The channel can hold every entry, so this never has to wait.
+        for (idx, line) in self.proxies.iter().enumerate() {
+            let parsed =
+                Proxy::from_str(line).map_err(|e| RejectedLine::new("--proxies", idx + 1, line, e));
+            let _ = tx.try_send(parsed);
         }
 
         Ok(rx)
@@ -0,0 +1,3 @@
+mod url_proxy_repository;
+
+pub use url_proxy_repository::UrlProxyRepository;
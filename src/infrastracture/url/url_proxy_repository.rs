@@ -0,0 +1,107 @@
+use crate::core::application::{Error as AppError, dto::InputFormat, ports::ProxyRepository};
+use crate::core::domain::{Proxy, RejectedLine};
+use crate::infrastracture::parser::forward_lines;
+use async_trait::async_trait;
+use reqwest::Client;
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufReader, DuplexStream, duplex};
+use tokio::sync::mpsc;
+use url::Url;
+
+const DOWNLOAD_BUFFER: usize = 64 * 1024;
+
+pub struct UrlProxyRepository {
+    urls: Vec<Url>,
+    format: InputFormat,
+    timeout: Duration,
+    header: Option<(String, String)>,
+}
+
+impl UrlProxyRepository {
+    pub fn new(
+        urls: Vec<Url>,
+        format: InputFormat,
+        timeout: u64,
+        header: Option<(String, String)>,
+    ) -> Self {
+        Self {
+            urls,
+            format,
+            timeout: Duration::from_secs(timeout),
+            header,
+        }
+    }
+
+    /// Pipes the response body into `writer` chunk by chunk, so parsing starts
+    /// before the whole list has been downloaded.
+    async fn download(
+        client: Client,
+        url: Url,
+        header: Option<(String, String)>,
+        mut writer: DuplexStream,
+    ) -> Result<(), AppError> {
+        let download_error =
+            |e: reqwest::Error| AppError::External(format!("Failed to download '{}': {}", url, e));
+
+        let mut request = client.get(url.clone());
+        if let Some((name, value)) = header {
+            request = request.header(name, value);
+        }
+
+        let mut response = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(download_error)?;
+
+        while let Some(chunk) = response.chunk().await.map_err(download_error)? {
+            if writer.write_all(&chunk).await.is_err() {
+                // The parser side is gone, nobody is waiting for the rest.
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProxyRepository for UrlProxyRepository {
+    async fn stream_proxies(
+        &self,
+    ) -> Result<mpsc::Receiver<Result<Proxy, RejectedLine>>, AppError> {
+        let client = Client::builder()
+            .connect_timeout(self.timeout)
+            .read_timeout(self.timeout)
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()
+            .map_err(|e| AppError::External(e.to_string()))?;
+
+        let (tx, rx) = mpsc::channel::<Result<Proxy, RejectedLine>>(100);
+        let urls = self.urls.clone();
+        let format = self.format;
+        let header = self.header.clone();
+
+        tokio::spawn(async move {
+            for url in urls {
+                let (writer, reader) = duplex(DOWNLOAD_BUFFER);
+                let source = url.to_string();
+
+                let (downloaded, _) = tokio::join!(
+                    Self::download(client.clone(), url, header.clone(), writer),
+                    forward_lines(BufReader::new(reader), source, format, tx.clone())
+                );
+
+                if let Err(e) = downloaded {
+                    eprintln!("{}", e);
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+}
@@ -1,24 +1,59 @@
-use clap::{ArgGroup, Parser, value_parser};
-use std::{path::PathBuf, u16};
+use clap::{ArgGroup, Args, Parser, Subcommand, value_parser};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 use url::Url;
 
 use super::error::{Error, cli_error};
+use crate::core::application::dto::{BackoffStrategy, InputFormat, JudgeFormat, JudgeUrl};
+use crate::core::domain::error::FailureKind;
+
+/// `--file` value that reads the proxy list from standard input.
+pub const STDIN_PATH: &str = "-";
+
+/// Largest `/bytes/N` download httpbin-style judges serve.
+pub const DEFAULT_THROUGHPUT_LIMIT: u64 = 102_400;
 
 #[derive(Debug, Parser)]
 #[command(version)]
+#[command(subcommand_negates_reqs = true)]
 #[command(
     group(ArgGroup::new("input")
         .required(true)
-        .multiple(false)
-        .args(["file", "proxies"]))
+        .multiple(true)
+        .args(["file", "proxies", "url"]))
 )]
 pub struct Cli {
-    /// Path to the file containing the list of proxies
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Path to a file containing a list of proxies ('-' for stdin, repeatable)
     #[arg(short, long)]
-    pub file: Option<PathBuf>,
+    pub file: Vec<PathBuf>,
+    /// Format of the proxy list file or URL (auto, plain, colon, csv, jsonl);
+    /// csv without a header row is read as host,port,scheme,username,password
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        default_value = "auto",
+        value_parser = parse_input_format
+    )]
+    pub format: InputFormat,
     /// List of proxies to test (comma-separated if more than one)
     #[arg(short, long)]
     pub proxies: Option<String>,
+    /// HTTP(S) URL of a proxy list to download (repeatable)
+    #[arg(short, long, value_name = "URL")]
+    pub url: Vec<Url>,
+    /// Extra header sent when downloading proxy lists, e.g. 'Authorization: Bearer <token>'
+    #[arg(long = "url-header", value_name = "HEADER")]
+    pub url_header: Option<String>,
+    /// Timeout for connecting to and reading from list URLs in seconds (1 - 300)
+    #[arg(
+        long = "url-timeout",
+        value_name = "SEC",
+        default_value_t = 30,
+        value_parser = value_parser!(u16).range(1..=300)
+    )]
+    pub url_timeout: u16,
     /// Timeout duration in second (1 - 10)
     #[arg(
         short, 
@@ -37,6 +72,103 @@ pub struct Cli {
         value_parser=value_parser!(u16).range(1..=500)
     )]
     pub max_concurrent: u16,
+    /// Time budget per proxy across all schemes, retries, samples and throughput in seconds (1 - 120)
+    #[arg(
+        long,
+        value_name = "SEC",
+        default_value_t = 15,
+        value_parser = value_parser!(u16).range(1..=120)
+    )]
+    pub deadline: u16,
+    /// Schemes of a single proxy tested at the same time (1 - 5)
+    #[arg(
+        long = "scheme-concurrency",
+        value_name = "NUM",
+        default_value_t = 3,
+        value_parser = value_parser!(u8).range(1..=5)
+    )]
+    pub scheme_concurrency: u8,
+    /// Report every scheme a proxy supports instead of only the best one
+    #[arg(long = "all-schemes")]
+    pub all_schemes: bool,
+    /// Sequential latency probes per working scheme for min/median/p95/jitter (1 - 20)
+    #[arg(
+        long,
+        value_name = "NUM",
+        default_value_t = 1,
+        value_parser = value_parser!(u8).range(1..=20)
+    )]
+    pub samples: u8,
+    /// Download this many bytes through each working scheme to measure throughput
+    /// (1 - 102400, up to 104857600 with --throughput-url)
+    #[arg(
+        long,
+        value_name = "BYTES",
+        value_parser = value_parser!(u64).range(1..=104_857_600)
+    )]
+    pub throughput: Option<u64>,
+    /// URL serving the throughput payload (default: <judge>/bytes/<BYTES>)
+    #[arg(long = "throughput-url", value_name = "URL", requires = "throughput")]
+    pub throughput_url: Option<Url>,
+    /// Also upload the payload to measure upload throughput
+    #[arg(long, requires = "throughput")]
+    pub upload: bool,
+    /// URL accepting the uploaded payload as a POST body (default: <judge>/post)
+    #[arg(long = "upload-url", value_name = "URL", requires = "upload")]
+    pub upload_url: Option<Url>,
+    /// Total bytes the throughput stage may transfer in one run
+    #[arg(
+        long = "throughput-budget",
+        value_name = "BYTES",
+        requires = "throughput"
+    )]
+    pub throughput_budget: Option<u64>,
+    /// Let the measured download rate count towards the score
+    #[arg(long = "score-throughput", requires = "throughput")]
+    pub score_throughput: bool,
+    /// Attempts per scheme before giving up on it (1 - 10)
+    #[arg(
+        long = "max-attempts",
+        value_name = "NUM",
+        default_value_t = 3,
+        value_parser = value_parser!(u8).range(1..=10)
+    )]
+    pub max_attempts: u8,
+    /// Growth of the pause between attempts (constant, linear, exponential)
+    #[arg(
+        long,
+        value_name = "STRATEGY",
+        default_value = "linear",
+        value_parser = parse_backoff_strategy
+    )]
+    pub backoff: BackoffStrategy,
+    /// Base pause between attempts in milliseconds (0 - 10000)
+    #[arg(
+        long = "backoff-base",
+        value_name = "MS",
+        default_value_t = 100,
+        value_parser = value_parser!(u16).range(0..=10000)
+    )]
+    pub backoff_base: u16,
+    /// Randomize each pause by up to 50% so retries do not move in lockstep
+    #[arg(long)]
+    pub jitter: bool,
+    /// Failure kinds worth another attempt (comma-separated)
+    #[arg(
+        long = "retry-on",
+        value_name = "KIND",
+        value_enum,
+        ignore_case = true,
+        value_delimiter = ',',
+        default_values_t = [
+            FailureKind::Timeout,
+            FailureKind::ConnectionReset,
+            FailureKind::ConnectionFailed,
+            FailureKind::JudgeUnreachable,
+            FailureKind::InvalidResponse,
+        ]
+    )]
+    pub retry_on: Vec<FailureKind>,
     /// Output directory for results
     #[arg(
         long = "out-dir",
@@ -44,6 +176,138 @@ pub struct Cli {
         default_value = "./proxy-results"
     )]
     pub out_dir: Option<PathBuf>,
+    /// Only report and write proxies in these countries, by exit IP or else by
+    /// proxy address (ISO codes, comma-separated)
+    #[arg(
+        long,
+        value_name = "CC",
+        value_delimiter = ',',
+        requires = "geoip",
+        value_parser = parse_country_code
+    )]
+    pub country: Vec<String>,
+    /// Write unparsable input lines to this file
+    #[arg(long, value_name = "FILE")]
+    pub rejects: Option<PathBuf>,
+    /// Print only working proxy URLs to stdout and everything else to stderr
+    /// (implied when stdout is not a terminal)
+    #[arg(long)]
+    pub plain: bool,
+    /// Abort on the first input line that cannot be parsed
+    #[arg(long)]
+    pub strict: bool,
+    /// Also treat hostnames that resolve to the same IP as duplicates
+    #[arg(long = "dedup-resolve")]
+    pub dedup_resolve: bool,
+    /// Report SOCKS auth methods and reply codes using a native handshake
+    #[arg(long = "socks-probe")]
+    pub socks_probe: bool,
+    /// Judge URL echoing client IP and headers, optionally per scheme (repeatable)
+    #[arg(long, value_name = "[SCHEME=]URL", value_parser = JudgeUrl::parse)]
+    pub judge: Vec<JudgeUrl>,
+    /// Judge URL echoing only the client IP, optionally per scheme (repeatable)
+    #[arg(long = "ip-judge", value_name = "[SCHEME=]URL", value_parser = JudgeUrl::parse)]
+    pub ip_judge: Vec<JudgeUrl>,
+    /// Response format of the judges (auto, plain, httpbin, azenv)
+    #[arg(
+        long = "judge-format",
+        value_name = "FORMAT",
+        default_value = "auto",
+        value_parser = parse_judge_format
+    )]
+    pub judge_format: JudgeFormat,
+    /// Our own public IP, skipping the lookup (repeatable for several egress IPs)
+    #[arg(long = "real-ip", value_name = "IP", conflicts_with = "real_ip_url")]
+    pub real_ip: Vec<IpAddr>,
+    /// URL used to look up our own public IP without a proxy (repeatable, all are queried)
+    #[arg(long = "real-ip-url", value_name = "URL")]
+    pub real_ip_url: Vec<Url>,
+    /// MaxMind-format (mmdb) database to locate proxies and their exit IPs offline
+    #[arg(long, value_name = "FILE")]
+    pub geoip: Option<PathBuf>,
+    /// Accept invalid or self-signed TLS certificates from judges
+    #[arg(long = "judge-insecure")]
+    pub judge_insecure: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run a judge server echoing the client IP and request headers
+    Judge(JudgeArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct JudgeArgs {
+    /// Address to serve plain HTTP on
+    #[arg(long, value_name = "ADDR", default_value = "0.0.0.0:8080")]
+    pub listen: SocketAddr,
+    /// Address to serve HTTPS on
+    #[arg(long = "tls-listen", value_name = "ADDR")]
+    pub tls_listen: Option<SocketAddr>,
+    /// PEM certificate chain for HTTPS (self-signed if omitted)
+    #[arg(long, value_name = "FILE", requires_all = ["key", "tls_listen"])]
+    pub cert: Option<PathBuf>,
+    /// PEM private key matching --cert
+    #[arg(long, value_name = "FILE", requires = "cert")]
+    pub key: Option<PathBuf>,
+    /// Subject name of the self-signed certificate (repeatable)
+    #[arg(long = "tls-name", value_name = "NAME", default_value = "localhost")]
+    pub tls_name: Vec<String>,
+}
+
+fn parse_input_format(s: &str) -> Result<InputFormat, String> {
+    InputFormat::parse(s).ok_or_else(|| {
+        let formats: Vec<String> = InputFormat::get_all_format()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        format!(
+            "invalid format '{}' [possible values: {}]",
+            s,
+            formats.join(", ")
+        )
+    })
+}
+
+fn parse_judge_format(s: &str) -> Result<JudgeFormat, String> {
+    JudgeFormat::parse(s).ok_or_else(|| {
+        let formats: Vec<String> = JudgeFormat::get_all_format()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        format!(
+            "invalid format '{}' [possible values: {}]",
+            s,
+            formats.join(", ")
+        )
+    })
+}
+
+fn parse_backoff_strategy(s: &str) -> Result<BackoffStrategy, String> {
+    BackoffStrategy::parse(s).ok_or_else(|| {
+        let strategies: Vec<String> = BackoffStrategy::get_all_strategy()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        format!(
+            "invalid strategy '{}' [possible values: {}]",
+            s,
+            strategies.join(", ")
+        )
+    })
+}
+
+fn parse_country_code(s: &str) -> Result<String, String> {
+    let code = s.trim();
+
+    if code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(code.to_uppercase())
+    } else {
+        Err(format!(
+            "invalid country '{}' [expected a two-letter ISO code]",
+            s
+        ))
+    }
 }
 
 impl Cli {
@@ -1,3 +1,4 @@
+use super::args::DEFAULT_THROUGHPUT_LIMIT;
 use super::help::cli_help;
 use clap::error::ErrorKind;
 use colored::*;
@@ -11,6 +12,9 @@ pub enum ValidationError {
     NotAFile(PathBuf),
     NotADirectory(PathBuf),
     DirectoryCreationFailed(PathBuf, std::io::Error),
+    UnsupportedUrl(String),
+    InvalidHeader(String),
+    PayloadTooLarge(u64),
 }
 
 #[derive(Debug)]
@@ -105,6 +109,42 @@ impl fmt::Display for ValidationError {
                         .bright_red()
                 )
             }
+            ValidationError::UnsupportedUrl(url) => {
+                write!(
+                    f,
+                    "{} {}",
+                    error_head,
+                    format!(
+                        "Unsupported URL '{}'. Only http and https are allowed.",
+                        url
+                    )
+                    .bright_red()
+                )
+            }
+            ValidationError::InvalidHeader(header) => {
+                write!(
+                    f,
+                    "{} {}",
+                    error_head,
+                    format!(
+                        "Invalid header '{}'. Expected format: 'Name: value'",
+                        header
+                    )
+                    .bright_red()
+                )
+            }
+            ValidationError::PayloadTooLarge(bytes) => {
+                write!(
+                    f,
+                    "{} {}",
+                    error_head,
+                    format!(
+                        "A throughput payload of {} bytes needs --throughput-url; the default judge serves at most {} bytes.",
+                        bytes, DEFAULT_THROUGHPUT_LIMIT
+                    )
+                    .bright_red()
+                )
+            }
         }
     }
 }
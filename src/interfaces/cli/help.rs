@@ -1,5 +1,5 @@
-use clap::Arg;
 use clap::CommandFactory;
+use clap::{Arg, Command};
 use colored::*;
 use heck::{AsSnakeCase, AsTitleCase};
 use rand::seq::IndexedRandom;
@@ -47,9 +47,21 @@ fn placeholder_format(arg: &Arg, has_value_arg: bool) -> String {
     out
 }
 
+/// Help for the subcommand named on the command line, or for the main command.
+fn help_command() -> Command {
+    let mut cmd = Cli::command();
+    cmd.build();
+
+    let subcommand = std::env::args()
+        .skip(1)
+        .find_map(|arg| cmd.find_subcommand(&arg).cloned());
+
+    subcommand.unwrap_or(cmd)
+}
+
 pub fn cli_help() {
     let mut custom_help_buff = String::new();
-    let mut cmd = Cli::command();
+    let mut cmd = help_command();
     let args: Vec<_> = cmd
         .get_arguments()
         .filter(|a| a.get_short().is_some() || a.get_long().is_some())
@@ -72,6 +84,31 @@ pub fn cli_help() {
         cmd.render_usage().to_string().replace("Usage: ", "").cyan()
     )
     .unwrap();
+
+    let subcommands: Vec<_> = cmd
+        .get_subcommands()
+        .filter(|sub| sub.get_name() != "help")
+        .collect();
+    if !subcommands.is_empty() {
+        writeln!(custom_help_buff, "\n{}:", "Commands".blue().bold()).unwrap();
+        let max_len_name = subcommands
+            .iter()
+            .fold(0, |max_len, sub| max_len.max(sub.get_name().len()));
+
+        for sub in subcommands {
+            writeln!(
+                custom_help_buff,
+                "  {}{} {}",
+                sub.get_name().cyan(),
+                " ".repeat(max_len_name - sub.get_name().len()),
+                sub.get_about()
+                    .map_or(String::new(), |about| about.to_string())
+                    .white()
+            )
+            .unwrap();
+        }
+    }
+
     writeln!(custom_help_buff, "\n{}:", "Options".blue().bold()).unwrap();
 
     for arg in cmd
@@ -80,7 +117,11 @@ pub fn cli_help() {
     {
         let placeholder_format = placeholder_format(arg, has_value_arg);
         let is_flag_type = arg.get_action().takes_values();
-        let required = arg.is_required_set().then_some(" (required)").unwrap_or("");
+        let required = if arg.is_required_set() {
+            " (required)"
+        } else {
+            ""
+        };
 
         arg.get_help().inspect(|help| {
             let mut flags = String::new();
@@ -90,11 +131,11 @@ pub fn cli_help() {
                 .map_or_else(|| "    ".to_string(), |short| format!("-{}", short));
             flags.push_str(&short_flag);
             arg.get_long().inspect(|long| {
-                let placeholder_pad = is_flag_type
-                    .then(|| {
-                        " ".repeat(max_len_long_flag_col - (long.len() + placeholder_format.len()))
-                    })
-                    .unwrap_or_else(|| " ".repeat(max_len_long_flag_col - long.len()));
+                let placeholder_pad = if is_flag_type {
+                    " ".repeat(max_len_long_flag_col - (long.len() + placeholder_format.len()))
+                } else {
+                    " ".repeat(max_len_long_flag_col - long.len())
+                };
 
                 if !flags.is_empty() && arg.get_short().is_some() {
                     flags.push_str(", ")
@@ -106,17 +147,21 @@ pub fn cli_help() {
                 ));
             });
 
-            let arg_default = arg
+            let defaults: Vec<_> = arg
                 .get_default_values()
-                .get(0)
-                .map_or("".to_string(), |default| {
-                    format!(
-                        " {} {} {}",
-                        "[default:".dimmed(),
-                        default.to_string_lossy().yellow(),
-                        "]".dimmed()
-                    )
-                });
+                .iter()
+                .map(|default| default.to_string_lossy())
+                .collect();
+            let arg_default = if defaults.is_empty() {
+                "".to_string()
+            } else {
+                format!(
+                    " {} {} {}",
+                    "[default:".dimmed(),
+                    defaults.join(",").yellow(),
+                    "]".dimmed()
+                )
+            };
 
             writeln!(
                 custom_help_buff,
@@ -3,4 +3,4 @@ mod error;
 mod help;
 mod validate;
 
-pub use args::Cli;
+pub use args::{Cli, Command, JudgeArgs, STDIN_PATH};
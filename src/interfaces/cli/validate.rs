@@ -1,21 +1,76 @@
-use super::Cli;
+use super::args::DEFAULT_THROUGHPUT_LIMIT;
 use super::error::{Error, ValidationError};
+use super::{Cli, Command, JudgeArgs, STDIN_PATH};
 use std::fs;
+use std::path::Path;
+
+fn validate_file(file: &Path) -> Result<(), Error> {
+    if !file.exists() {
+        return Err(Error::Validation(ValidationError::FileNotFound(
+            file.to_path_buf(),
+        )));
+    };
+
+    if !file.is_file() {
+        return Err(Error::Validation(ValidationError::NotAFile(
+            file.to_path_buf(),
+        )));
+    }
+
+    Ok(())
+}
+
+impl JudgeArgs {
+    pub fn validate(&self) -> Result<(), Error> {
+        for file in self.cert.iter().chain(self.key.iter()) {
+            validate_file(file)?;
+        }
+        Ok(())
+    }
+}
 
 impl Cli {
     pub fn validate(&self) -> Result<(), Error> {
-        if let Some(file) = &self.file {
-            if !file.exists() {
-                return Err(Error::Validation(ValidationError::FileNotFound(
-                    file.to_path_buf(),
-                )));
-            };
-
-            if !file.is_file() {
-                return Err(Error::Validation(ValidationError::NotAFile(
-                    file.to_path_buf(),
-                )));
-            }
+        if let Some(Command::Judge(args)) = &self.command {
+            return args.validate();
+        }
+
+        for file in self
+            .file
+            .iter()
+            .filter(|f| f.as_os_str() != STDIN_PATH)
+            .chain(self.geoip.iter())
+        {
+            validate_file(file)?;
+        }
+
+        if let Some(url) = self
+            .url
+            .iter()
+            .chain(self.real_ip_url.iter())
+            .chain(self.throughput_url.iter())
+            .chain(self.upload_url.iter())
+            .find(|url| !matches!(url.scheme(), "http" | "https"))
+        {
+            return Err(Error::Validation(ValidationError::UnsupportedUrl(
+                url.to_string(),
+            )));
+        }
+
+        if let Some(payload) = self
+            .throughput
+            .filter(|p| *p > DEFAULT_THROUGHPUT_LIMIT && self.throughput_url.is_none())
+        {
+            return Err(Error::Validation(ValidationError::PayloadTooLarge(payload)));
+        }
+
+        if let Some(header) = self.url_header.as_ref().filter(|h| {
+            h.split_once(':')
+                .is_none_or(|(name, _)| name.trim().is_empty())
+        }) {
+            return Err(Error::Validation(ValidationError::InvalidHeader(
+                header.to_string(),
+            )));
         }
 
         if let Some(out_dir) = &self.out_dir {
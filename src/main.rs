@@ -2,35 +2,184 @@ mod core;
 mod infrastracture;
 mod interfaces;
 
-use core::application::dto::AppInputDTO;
-use interfaces::cli::Cli;
+use core::application::dto::{AppInputDTO, JudgeServerInputDTO};
+use interfaces::cli::{Cli, Command};
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
-    core::application::use_cases::{self, ProxyTester},
+    core::application::{
+        ports::{GeoIpPort, HostResolver, ProxyRepository, ResultWriter, SocksProbePort},
+        use_cases::{ProxyTester, RunOptions},
+    },
+    core::domain::ProxyScheme,
     infrastracture::{
-        file::FileProxyRepository, proxy_test::ReqwestProxyTestService,
+        composite::CompositeProxyRepository,
+        dns::TokioHostResolver,
+        file::FileProxyRepository,
+        geoip::MaxMindGeoIp,
+        judge_server::{JudgeServer, load_tls_config},
+        output::FileResultWriter,
+        proxy_test::{
+            JudgeConfig, RealIpResolver, ReqwestProxyTestService, RetryPolicy, TestPlan,
+            ThroughputProbe, TokioSocksProber,
+        },
+        stdin::StdinProxyRepository,
         string::StringProxyRepository,
+        url::UrlProxyRepository,
     },
 };
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse_and_validate();
+    let mut cli = Cli::parse_and_validate();
+
+    if let Some(Command::Judge(args)) = cli.command.take() {
+        return run_judge(JudgeServerInputDTO::from_args(args)).await;
+    }
+
     let app_dto = AppInputDTO::from_cli(cli);
-    let tester = Arc::new(ReqwestProxyTestService::new(app_dto.timeout));
+    let judge = JudgeConfig::new(
+        &app_dto.judges,
+        &app_dto.ip_judges,
+        app_dto.real_ip_urls.clone(),
+        app_dto.judge_format,
+        app_dto.judge_insecure,
+    );
+    let prober = app_dto.socks_probe.then(|| {
+        Arc::new(TokioSocksProber::new(
+            app_dto.timeout,
+            judge.ip_url(&ProxyScheme::Socks5h),
+        )) as Arc<dyn SocksProbePort>
+    });
+    let retry = RetryPolicy::new(
+        app_dto.max_attempts,
+        Duration::from_millis(app_dto.backoff_base),
+        app_dto.backoff,
+        app_dto.jitter,
+        app_dto.retry_on.clone(),
+    );
+    let real_ips = RealIpResolver::new(app_dto.real_ips.clone(), &judge, app_dto.timeout);
+    // Our own `judge` serves `/bytes/N` and `/post` like httpbin does.
+    let throughput = app_dto.throughput.map(|payload| {
+        let judge_url = judge.header_url(&ProxyScheme::Http);
+        ThroughputProbe::new(
+            payload,
+            app_dto.throughput_url.clone().unwrap_or_else(|| {
+                judge_url
+                    .join(&format!("/bytes/{}", payload))
+                    .expect("an absolute path joins onto any judge URL")
+            }),
+            app_dto.upload.then(|| {
+                app_dto.upload_url.clone().unwrap_or_else(|| {
+                    judge_url
+                        .join("/post")
+                        .expect("an absolute path joins onto any judge URL")
+                })
+            }),
+            app_dto.throughput_budget,
+            app_dto.score_throughput,
+        )
+    });
+    let geo_ip = match app_dto.geoip.as_deref().map(MaxMindGeoIp::open).transpose() {
+        Ok(geo_ip) => geo_ip.map(|g| Arc::new(g) as Arc<dyn GeoIpPort>),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let tester = match ReqwestProxyTestService::new(
+        app_dto.timeout,
+        judge,
+        real_ips,
+        retry,
+        TestPlan::new(
+            app_dto.deadline,
+            app_dto.scheme_concurrency,
+            app_dto.all_schemes,
+            app_dto.samples,
+        ),
+        throughput,
+    ) {
+        Ok(tester) => Arc::new(tester),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let writer = (app_dto.out_dir.is_some() || app_dto.rejects.is_some()).then(|| {
+        Arc::new(FileResultWriter::new(
+            app_dto.out_dir.clone(),
+            app_dto.rejects.clone(),
+        )) as Arc<dyn ResultWriter>
+    });
 
-    if let Some(file) = app_dto.file {
-        let file_repo = Arc::new(FileProxyRepository::new(file));
-        let use_case = ProxyTester::new(file_repo, tester.clone(), app_dto.max_concurrent);
+    let mut sources: Vec<Arc<dyn ProxyRepository>> = Vec::new();
+
+    if app_dto.stdin {
+        sources.push(Arc::new(StdinProxyRepository::new(app_dto.format)));
+    }
+
+    for file in app_dto.files {
+        sources.push(Arc::new(FileProxyRepository::new(file, app_dto.format)));
+    }
 
-        let _ = use_case.execute().await;
+    if !app_dto.urls.is_empty() {
+        sources.push(Arc::new(UrlProxyRepository::new(
+            app_dto.urls,
+            app_dto.format,
+            app_dto.url_timeout,
+            app_dto.url_header,
+        )));
     }
 
     if let Some(proxies) = app_dto.proxies {
-        let string_repo = Arc::new(StringProxyRepository::new(&proxies));
-        let use_cases = ProxyTester::new(string_repo, tester.clone(), app_dto.max_concurrent);
+        sources.push(Arc::new(StringProxyRepository::new(&proxies)));
+    }
+
+    let repo = Arc::new(CompositeProxyRepository::new(sources));
+    // Hostnames are also resolved to look up where they are.
+    let resolver = (app_dto.dedup_resolve || geo_ip.is_some())
+        .then(|| Arc::new(TokioHostResolver::new(app_dto.timeout)) as Arc<dyn HostResolver>);
+
+    let use_case = ProxyTester::new(
+        repo,
+        tester,
+        writer,
+        RunOptions::new(
+            app_dto.max_concurrent,
+            app_dto.strict,
+            app_dto.plain,
+            app_dto.dedup_resolve,
+            app_dto.countries,
+        ),
+        resolver,
+        prober,
+        geo_ip,
+    );
+
+    if let Err(e) = use_case.execute().await {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run_judge(judge_dto: JudgeServerInputDTO) {
+    let tls = judge_dto.tls_listen.map(|addr| {
+        let cert = judge_dto
+            .cert
+            .as_ref()
+            .map(|(cert, key)| (cert.as_path(), key.as_path()));
+        load_tls_config(cert, &judge_dto.tls_names).map(|config| (addr, config))
+    });
+
+    let result = match tls.transpose() {
+        Ok(tls) => JudgeServer::new(judge_dto.listen, tls).run().await,
+        Err(e) => Err(e),
+    };
 
-        let _ = use_cases.execute().await;
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }